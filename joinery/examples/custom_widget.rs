@@ -119,6 +119,7 @@ impl Widget for CustomWidget {
             &mut scratch_scene,
             Affine::rotate(std::f64::consts::FRAC_PI_4).then_translate((80.0, 40.0).into()),
             &text_layout,
+            &self.0,
         );
 
         // Let's burn some CPU to make a (partially transparent) image buffer
@@ -0,0 +1,47 @@
+//! A prompt drawn below the shell's cursor instead of taking over the whole
+//! terminal, which stays in the scrollback once answered.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use joinery::app_driver::{AppDriver, DriverCtx};
+use joinery::app_launcher::AppLauncher;
+use joinery::event_loop_runner::Viewport;
+use joinery::render_root::WindowSizePolicy;
+use joinery::widget::{Flex, Label, RootWidget, Textbox};
+use joinery::{Action, WidgetId};
+
+struct Driver {
+    name: Rc<RefCell<Option<String>>>,
+}
+
+impl AppDriver for Driver {
+    fn on_action(&mut self, ctx: &mut DriverCtx<'_>, _widget_id: WidgetId, action: Action) {
+        if let Action::TextEntered(name) = action {
+            *self.name.borrow_mut() = Some(name);
+            ctx.request_quit();
+        }
+    }
+}
+
+pub fn main() {
+    let textbox_id = WidgetId::next();
+    let main_widget = Flex::row()
+        .with_child(Label::new("What's your name?"))
+        .with_spacer(1.0)
+        .with_child_id(Textbox::new(""), textbox_id);
+
+    let name = Rc::new(RefCell::new(None));
+    AppLauncher::new()
+        .viewport(Viewport::Inline(10))
+        .size_policy(WindowSizePolicy::Content)
+        .initial_focus(textbox_id)
+        .launch(RootWidget::new(main_widget), Driver { name: name.clone() })
+        .unwrap();
+
+    // The terminal is restored by now, with the prompt left above the cursor.
+    match name.take() {
+        Some(name) => println!("Hello, {name}!"),
+        None => println!("Goodbye!"),
+    }
+}
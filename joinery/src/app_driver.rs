@@ -1,8 +1,15 @@
 // Copyright 2024 the Xilem Authors
 // SPDX-License-Identifier: Apache-2.0
 
+use std::time::Duration;
+
+use dpi::PhysicalSize;
+
+use crate::ext_event::ExtEventSink;
+use crate::terminal::event::KeyEvent;
+use crate::terminal::keyboard::{Key, ModifiersState};
 use crate::widget::WidgetMut;
-use crate::{Action, Widget, WidgetId};
+use crate::{Action, Handled, Widget, WidgetId};
 
 // xilem::App will implement AppDriver
 
@@ -14,15 +21,145 @@ pub struct DriverCtx<'a> {
     // expedience only while better solutions are devised.
     #[doc(hidden)]
     pub main_root_widget: WidgetMut<'a, Box<dyn Widget>>,
+    pub(crate) quit_requested: bool,
+    pub(crate) ext_event_sink: ExtEventSink,
 }
 
+/// A function run on the event loop's thread, submitted from another thread
+/// with [`ExtEventSink::run_on_driver`].
+pub struct DriverCallback(Box<dyn FnOnce(&mut DriverCtx<'_>) + Send + 'static>);
+
 pub trait AppDriver {
     fn on_action(&mut self, ctx: &mut DriverCtx<'_>, widget_id: WidgetId, action: Action);
+
+    /// Called once the widget tree had its first layout, before it is drawn.
+    ///
+    /// This is the place to load the initial data of the app, possibly in the
+    /// background through [`DriverCtx::ext_event_sink`].
+    fn on_start(&mut self, ctx: &mut DriverCtx<'_>) {
+        let _ = ctx;
+    }
+
+    /// Called when the terminal was resized to `size`, in cells.
+    fn on_resize(&mut self, ctx: &mut DriverCtx<'_>, size: PhysicalSize<u32>) {
+        let _ = (ctx, size);
+    }
+
+    /// The interval at which [`on_tick`](Self::on_tick) is called, or `None`
+    /// to not call it.
+    ///
+    /// This is checked after each iteration of the event loop, so the interval
+    /// can change while the app runs. Intervals shorter than the time between
+    /// two animation frames, set with
+    /// [`AppLauncher::max_fps`](crate::app_launcher::AppLauncher::max_fps), are
+    /// rounded up to it.
+    fn tick_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Called every [`tick_interval`](Self::tick_interval).
+    ///
+    /// Ticks missed while the event loop was busy are skipped rather than
+    /// called in a burst.
+    fn on_tick(&mut self, ctx: &mut DriverCtx<'_>) {
+        let _ = ctx;
+    }
+
+    /// Called for key presses the widgets left unhandled, to implement
+    /// global shortcuts.
+    ///
+    /// Returning [`Handled::Yes`] prevents the default behavior of the key,
+    /// such as closing the app for a [quit key](Self::is_quit_key).
+    fn on_unhandled_key(
+        &mut self,
+        ctx: &mut DriverCtx<'_>,
+        event: &KeyEvent,
+        mods: ModifiersState,
+    ) -> Handled {
+        let _ = (ctx, event, mods);
+        Handled::No
+    }
+
+    /// Called when the terminal gains or loses focus.
+    ///
+    /// Terminals which don't report focus changes never call this.
+    fn on_focus_change(&mut self, ctx: &mut DriverCtx<'_>, focused: bool) {
+        let _ = (ctx, focused);
+    }
+
+    /// Called once the event loop stopped, before the terminal is restored.
+    ///
+    /// This is the place to persist the state of the app.
+    fn on_quit(&mut self, ctx: &mut DriverCtx<'_>) {
+        let _ = ctx;
+    }
+
+    /// Whether a key press left unhandled by the widgets should close the app.
+    ///
+    /// By default, this is <kbd>Ctrl</kbd>+<kbd>C</kbd> or
+    /// <kbd>Ctrl</kbd>+<kbd>Q</kbd>. Since focused widgets see key presses
+    /// first, a textbox copying its selection with <kbd>Ctrl</kbd>+<kbd>C</kbd>
+    /// doesn't close the app.
+    fn is_quit_key(&self, event: &KeyEvent, mods: ModifiersState) -> bool {
+        match &event.logical_key {
+            Key::Character(chr) => {
+                mods == ModifiersState::CONTROL
+                    && (chr.eq_ignore_ascii_case("c") || chr.eq_ignore_ascii_case("q"))
+            }
+            _ => false,
+        }
+    }
+
+    /// Called when the user asks to close the app, with a quit key or through
+    /// [`EventCtx::request_quit`](crate::EventCtx::request_quit).
+    ///
+    /// Returning `false` keeps the app open, for instance to ask whether
+    /// unsaved changes should be saved first.
+    fn on_close_requested(&mut self, ctx: &mut DriverCtx<'_>) -> bool {
+        let _ = ctx;
+        true
+    }
 }
 
 impl<'a> DriverCtx<'a> {
+    pub(crate) fn new(
+        main_root_widget: WidgetMut<'a, Box<dyn Widget>>,
+        ext_event_sink: ExtEventSink,
+    ) -> Self {
+        DriverCtx {
+            main_root_widget,
+            quit_requested: false,
+            ext_event_sink,
+        }
+    }
+
     /// Return a [`WidgetMut`] to the root widget.
     pub fn get_root<W: Widget>(&mut self) -> WidgetMut<'_, W> {
         self.main_root_widget.downcast()
     }
+
+    /// Close the app once the current callback returns.
+    ///
+    /// Unlike a quit key, this doesn't go through
+    /// [`AppDriver::on_close_requested`].
+    pub fn request_quit(&mut self) {
+        self.quit_requested = true;
+    }
+
+    /// A handle that other threads can use to send actions, promise results
+    /// and callbacks to the app.
+    pub fn ext_event_sink(&self) -> ExtEventSink {
+        self.ext_event_sink.clone()
+    }
+}
+
+impl DriverCallback {
+    pub fn new(callback: impl FnOnce(&mut DriverCtx<'_>) + Send + 'static) -> Self {
+        DriverCallback(Box::new(callback))
+    }
+
+    /// Run the callback with `ctx`.
+    pub fn run(self, ctx: &mut DriverCtx<'_>) {
+        (self.0)(ctx);
+    }
 }
@@ -0,0 +1,274 @@
+//! Configuring the terminal and the widget tree before running an app.
+
+use std::fmt;
+use std::io;
+
+use tracing::Dispatch;
+
+use crate::app_driver::AppDriver;
+use crate::event_loop_runner::{self, Viewport};
+use crate::render_root::WindowSizePolicy;
+use crate::terminal::backend::{StdoutTerminal, TerminalBackend};
+use crate::vello::{peniko::Color, ColorDepth, SubCellMode};
+use crate::{CellMetrics, Widget, WidgetId};
+
+/// Runs an app with the given settings.
+///
+/// ```no_run
+/// # use joinery::app_driver::{AppDriver, DriverCtx};
+/// # use joinery::{Action, WidgetId};
+/// # struct Driver;
+/// # impl AppDriver for Driver {
+/// #     fn on_action(&mut self, _: &mut DriverCtx<'_>, _: WidgetId, _: Action) {}
+/// # }
+/// use joinery::app_launcher::AppLauncher;
+/// use joinery::event_loop_runner::Viewport;
+/// use joinery::widget::{Label, RootWidget};
+///
+/// AppLauncher::new()
+///     .viewport(Viewport::Inline(3))
+///     .mouse_capture(false)
+///     .launch(RootWidget::new(Label::new("Hello")), Driver)
+///     .unwrap();
+/// ```
+#[must_use = "the app only runs once launched"]
+pub struct AppLauncher {
+    pub(crate) options: LaunchOptions,
+}
+
+/// The settings of an [`AppLauncher`], read by the event loop.
+pub(crate) struct LaunchOptions {
+    pub(crate) viewport: Viewport,
+    pub(crate) mouse_capture: bool,
+    pub(crate) bracketed_paste: bool,
+    pub(crate) focus_reporting: bool,
+    pub(crate) max_fps: u32,
+    pub(crate) tracing: Tracing,
+    pub(crate) initial_focus: Option<WidgetId>,
+    pub(crate) size_policy: WindowSizePolicy,
+    pub(crate) cell_metrics: CellMetrics,
+    pub(crate) background_color: Color,
+    /// `None` to detect the color depth from the environment.
+    pub(crate) color_depth: Option<ColorDepth>,
+    pub(crate) sub_cell_mode: SubCellMode,
+}
+
+/// The tracing subscriber installed when the app is launched.
+pub(crate) enum Tracing {
+    /// Joinery's own subscriber, configured with `RUST_LOG`.
+    Default,
+    Subscriber(Dispatch),
+    /// Leave the global subscriber alone.
+    Disabled,
+}
+
+/// An error that stopped an app.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LaunchError {
+    /// The terminal couldn't be set up, for instance because the output isn't
+    /// a terminal.
+    Setup(io::Error),
+    /// Reading the terminal's input failed.
+    Input(io::Error),
+    /// Drawing to the terminal failed.
+    Output(io::Error),
+    /// The terminal couldn't be put back in its normal state once the app
+    /// stopped.
+    Restore(io::Error),
+}
+
+impl AppLauncher {
+    /// Settings for a fullscreen app with every terminal feature enabled.
+    pub fn new() -> Self {
+        AppLauncher {
+            options: LaunchOptions {
+                viewport: Viewport::default(),
+                mouse_capture: true,
+                bracketed_paste: true,
+                focus_reporting: true,
+                max_fps: 60,
+                tracing: Tracing::Default,
+                initial_focus: None,
+                size_policy: WindowSizePolicy::default(),
+                cell_metrics: CellMetrics::default(),
+                background_color: Color::BLACK,
+                color_depth: None,
+                sub_cell_mode: SubCellMode::default(),
+            },
+        }
+    }
+
+    /// Set the part of the terminal the app is drawn into.
+    pub fn viewport(mut self, viewport: Viewport) -> Self {
+        self.options.viewport = viewport;
+        self
+    }
+
+    /// Set whether the app receives mouse events.
+    ///
+    /// While it does, the terminal's own mouse handling, such as selecting
+    /// text or scrolling through the scrollback, is usually unavailable.
+    pub fn mouse_capture(mut self, enabled: bool) -> Self {
+        self.options.mouse_capture = enabled;
+        self
+    }
+
+    /// Set whether pasted text is received as a single
+    /// [`TextEvent::Paste`](crate::TextEvent::Paste), instead of one key press
+    /// per character.
+    pub fn bracketed_paste(mut self, enabled: bool) -> Self {
+        self.options.bracketed_paste = enabled;
+        self
+    }
+
+    /// Set whether the terminal reports when it gains or loses focus.
+    pub fn focus_reporting(mut self, enabled: bool) -> Self {
+        self.options.focus_reporting = enabled;
+        self
+    }
+
+    /// Set the highest rate at which animation frames are run, in frames per
+    /// second.
+    ///
+    /// Frames only run while a widget requests them: otherwise, the event loop
+    /// sleeps until the next input or timer.
+    pub fn max_fps(mut self, max_fps: u32) -> Self {
+        self.options.max_fps = max_fps;
+        self
+    }
+
+    /// Install `subscriber` as the global tracing subscriber, instead of the
+    /// default one configured with `RUST_LOG`.
+    ///
+    /// Nothing is installed if a global subscriber was already set.
+    pub fn tracing_subscriber(
+        mut self,
+        subscriber: impl tracing::Subscriber + Send + Sync + 'static,
+    ) -> Self {
+        self.options.tracing = Tracing::Subscriber(Dispatch::new(subscriber));
+        self
+    }
+
+    /// Don't install a global tracing subscriber.
+    pub fn without_tracing(mut self) -> Self {
+        self.options.tracing = Tracing::Disabled;
+        self
+    }
+
+    /// Focus the widget with the given id once the app starts.
+    pub fn initial_focus(mut self, widget_id: WidgetId) -> Self {
+        self.options.initial_focus = Some(widget_id);
+        self
+    }
+
+    /// Set how the root widget is sized.
+    ///
+    /// With [`WindowSizePolicy::Content`], an [inline](Viewport::Inline)
+    /// viewport only has as many lines as the root widget needs.
+    pub fn size_policy(mut self, size_policy: WindowSizePolicy) -> Self {
+        self.options.size_policy = size_policy;
+        self
+    }
+
+    /// Set the size of a terminal cell, in logical pixels.
+    pub fn cell_metrics(mut self, cell_metrics: CellMetrics) -> Self {
+        self.options.cell_metrics = cell_metrics;
+        self
+    }
+
+    /// Set the color drawn behind the widgets.
+    ///
+    /// This is the only color an app can set: widgets are drawn with the
+    /// colors of the [`theme`](crate::theme), which are constants.
+    pub fn background_color(mut self, color: Color) -> Self {
+        self.options.background_color = color;
+        self
+    }
+
+    /// Set the colors the terminal can show, instead of detecting them from
+    /// the environment.
+    pub fn color_depth(mut self, color_depth: ColorDepth) -> Self {
+        self.options.color_depth = Some(color_depth);
+        self
+    }
+
+    /// Set how shapes finer than a cell are drawn.
+    ///
+    /// This defaults to [`SubCellMode::Quadrant`]. [`SubCellMode::HalfBlock`]
+    /// suits terminal fonts without quadrant characters, such as the Linux
+    /// console's.
+    pub fn sub_cell_mode(mut self, sub_cell_mode: SubCellMode) -> Self {
+        self.options.sub_cell_mode = sub_cell_mode;
+        self
+    }
+
+    /// Run the app in the terminal of the process until it is closed.
+    pub fn launch(
+        self,
+        root_widget: impl Widget,
+        app_driver: impl AppDriver + 'static,
+    ) -> Result<(), LaunchError> {
+        self.launch_with_backend(StdoutTerminal::new(), root_widget, app_driver)
+            .map(drop)
+    }
+
+    /// Run the app in `backend` until it is closed, and give the backend back.
+    ///
+    /// This draws the app to another terminal than the one of the process,
+    /// such as a PTY, a socket, or a ratatui
+    /// [`TestBackend`](ratatui::backend::TestBackend) whose buffer tests can
+    /// inspect once the app closed.
+    pub fn launch_with_backend<B: TerminalBackend>(
+        self,
+        backend: B,
+        root_widget: impl Widget,
+        app_driver: impl AppDriver + 'static,
+    ) -> Result<B, LaunchError> {
+        event_loop_runner::launch(backend, root_widget, Box::new(app_driver), self.options)
+    }
+
+    /// Run the app in a future, which can be driven by any async executor such
+    /// as tokio or smol.
+    ///
+    /// Unlike [`launch`](Self::launch), this doesn't block a thread while
+    /// waiting for input. Futures spawned on the same executor can send results
+    /// back to the app through an [`ExtEventSink`](crate::ExtEventSink),
+    /// obtained from [`DriverCtx::ext_event_sink`](crate::app_driver::DriverCtx::ext_event_sink).
+    #[cfg(feature = "async")]
+    pub async fn launch_async(
+        self,
+        root_widget: impl Widget,
+        app_driver: impl AppDriver + 'static,
+    ) -> Result<(), LaunchError> {
+        event_loop_runner::launch_async(root_widget, Box::new(app_driver), self.options).await
+    }
+}
+
+impl Default for AppLauncher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for LaunchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LaunchError::Setup(err) => write!(f, "failed to set the terminal up: {err}"),
+            LaunchError::Input(err) => write!(f, "failed to read terminal input: {err}"),
+            LaunchError::Output(err) => write!(f, "failed to draw to the terminal: {err}"),
+            LaunchError::Restore(err) => write!(f, "failed to restore the terminal: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for LaunchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LaunchError::Setup(err)
+            | LaunchError::Input(err)
+            | LaunchError::Output(err)
+            | LaunchError::Restore(err) => Some(err),
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! Mapping between logical pixels and terminal cells.
+
+use std::ops::Range;
+
+use dpi::PhysicalSize;
+use kurbo::{Point, Rect, Size};
+
+/// The size of a terminal cell, in logical pixels.
+///
+/// Widgets lay out and paint in logical pixels, but a terminal can only show
+/// whole cells, which are usually about twice as tall as they are wide. This
+/// is the single policy used to go from one to the other:
+///
+/// - Window sizes are measured in cells, and become logical sizes (and root
+///   [`BoxConstraints`](crate::BoxConstraints)) through [`logical_size`].
+/// - A pointer over a cell is at the [center](Self::cell_center) of that cell.
+/// - When rasterizing, a cell belongs to a filled shape if its center lies
+///   inside the shape. For a rect, this snaps every edge to the nearest cell
+///   boundary, see [`snap_rect`].
+/// - A stroke covers every cell its center line passes through, see
+///   [`cells_touching`].
+///
+/// [`logical_size`]: Self::logical_size
+/// [`snap_rect`]: Self::snap_rect
+/// [`cells_touching`]: Self::cells_touching
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CellMetrics {
+    /// The width of a cell, in logical pixels.
+    pub width: f64,
+    /// The height of a cell, in logical pixels.
+    pub height: f64,
+}
+
+impl CellMetrics {
+    /// One logical pixel per cell.
+    ///
+    /// With these metrics, sizes and positions can be written directly in
+    /// cells, which is mostly useful in tests.
+    pub const UNIT: CellMetrics = CellMetrics::new(1.0, 1.0);
+
+    /// Cells of 8 by 16 logical pixels.
+    ///
+    /// This roughly matches a monospace font at the text size used by the
+    /// [theme](crate::theme), so that the theme's sizes make sense in a
+    /// terminal.
+    pub const TERMINAL: CellMetrics = CellMetrics::new(8.0, 16.0);
+
+    /// Create metrics for cells of `width` by `height` logical pixels.
+    pub const fn new(width: f64, height: f64) -> CellMetrics {
+        CellMetrics { width, height }
+    }
+
+    /// The logical size of an area of `cells`.
+    pub fn logical_size(&self, cells: PhysicalSize<u32>) -> Size {
+        Size::new(
+            f64::from(cells.width) * self.width,
+            f64::from(cells.height) * self.height,
+        )
+    }
+
+    /// The number of cells needed to show an area of logical `size`.
+    ///
+    /// Partially covered cells are counted, so that no content is cut off.
+    pub fn cells_for_size(&self, size: Size) -> PhysicalSize<u32> {
+        PhysicalSize::new(
+            (size.width / self.width).ceil().max(0.0) as u32,
+            (size.height / self.height).ceil().max(0.0) as u32,
+        )
+    }
+
+    /// The center of the cell at `col` and `row`, in logical pixels.
+    pub fn cell_center(&self, col: i64, row: i64) -> Point {
+        Point::new(
+            (col as f64 + 0.5) * self.width,
+            (row as f64 + 0.5) * self.height,
+        )
+    }
+
+    /// Converts a point in logical pixels to (fractional) cell coordinates.
+    pub fn to_cells(&self, point: Point) -> Point {
+        Point::new(point.x / self.width, point.y / self.height)
+    }
+
+    /// The columns and rows of the cells whose centers lie inside `rect`.
+    pub fn cell_range(&self, rect: Rect) -> (Range<i64>, Range<i64>) {
+        let first = |v: f64, size: f64| (v / size - 0.5).ceil() as i64;
+        let cols = first(rect.x0, self.width)..first(rect.x1, self.width);
+        let rows = first(rect.y0, self.height)..first(rect.y1, self.height);
+        (cols, rows)
+    }
+
+    /// Snaps `rect` to the cell grid.
+    ///
+    /// The result covers exactly the cells of [`cell_range`](Self::cell_range).
+    /// It can be empty if `rect` doesn't contain the center of any cell.
+    pub fn snap_rect(&self, rect: Rect) -> Rect {
+        let (cols, rows) = self.cell_range(rect);
+        Rect::new(
+            cols.start as f64 * self.width,
+            rows.start as f64 * self.height,
+            cols.end.max(cols.start) as f64 * self.width,
+            rows.end.max(rows.start) as f64 * self.height,
+        )
+    }
+
+    /// The columns and rows of the cells touched by the outline of `rect`.
+    ///
+    /// Unlike [`cell_range`](Self::cell_range), this always contains at least
+    /// one cell, so that thin strokes and zero-length lines stay visible.
+    pub fn cells_touching(&self, rect: Rect) -> (Range<i64>, Range<i64>) {
+        let range = |v0: f64, v1: f64, size: f64| {
+            let start = (v0 / size).floor() as i64;
+            let end = ((v1 / size).ceil() as i64).max(start + 1);
+            start..end
+        };
+        (
+            range(rect.x0, rect.x1, self.width),
+            range(rect.y0, rect.y1, self.height),
+        )
+    }
+}
+
+impl Default for CellMetrics {
+    fn default() -> Self {
+        CellMetrics::TERMINAL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_round_trip() {
+        let metrics = CellMetrics::TERMINAL;
+        let cells = PhysicalSize::new(80, 24);
+        let size = metrics.logical_size(cells);
+        assert_eq!(size, Size::new(640.0, 384.0));
+        assert_eq!(metrics.cells_for_size(size), cells);
+        assert_eq!(
+            metrics.cells_for_size(Size::new(641.0, 383.0)),
+            PhysicalSize::new(81, 24)
+        );
+    }
+
+    #[test]
+    fn rects_snap_to_nearest_boundary() {
+        let metrics = CellMetrics::TERMINAL;
+        // A basic widget height of 18px covers a single row.
+        let rect = Rect::new(3.0, 0.0, 21.0, 18.0);
+        assert_eq!(metrics.cell_range(rect), (0..3, 0..1));
+        assert_eq!(metrics.snap_rect(rect), Rect::new(0.0, 0.0, 24.0, 16.0));
+        let rect = Rect::new(0.0, 5.0, 8.0, 32.0);
+        assert_eq!(metrics.cell_range(rect), (0..1, 0..2));
+
+        // Too small to cover any cell center.
+        let rect = Rect::new(1.0, 1.0, 3.0, 7.0);
+        assert_eq!(metrics.snap_rect(rect).area(), 0.0);
+    }
+
+    #[test]
+    fn thin_outlines_touch_a_cell() {
+        let metrics = CellMetrics::TERMINAL;
+        let line = Rect::new(4.0, 20.0, 4.0, 20.0);
+        assert_eq!(metrics.cells_touching(line), (0..1, 1..2));
+        let rect = Rect::new(0.5, 0.5, 23.5, 31.5);
+        assert_eq!(metrics.cells_touching(rect), (0..3, 0..2));
+    }
+
+    #[test]
+    fn pointer_is_at_cell_center() {
+        let metrics = CellMetrics::TERMINAL;
+        assert_eq!(metrics.cell_center(2, 1), Point::new(20.0, 24.0));
+        assert_eq!(
+            metrics.to_cells(Point::new(20.0, 24.0)),
+            Point::new(2.5, 1.5)
+        );
+    }
+}
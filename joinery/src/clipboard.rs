@@ -0,0 +1,125 @@
+//! Access to the clipboard from widgets.
+
+use std::io::Write;
+
+/// A clipboard holding text.
+///
+/// Widgets reach the clipboard of their window through
+/// [`EventCtx::clipboard`](crate::EventCtx::clipboard).
+pub trait Clipboard {
+    /// Replaces the contents of the clipboard with `text`.
+    fn set_text(&mut self, text: String);
+
+    /// The text in the clipboard, if it can be read.
+    fn get_text(&mut self) -> Option<String>;
+}
+
+impl<C: Clipboard + ?Sized> Clipboard for Box<C> {
+    fn set_text(&mut self, text: String) {
+        (**self).set_text(text);
+    }
+
+    fn get_text(&mut self) -> Option<String> {
+        (**self).get_text()
+    }
+}
+
+/// A clipboard private to the app.
+///
+/// Text copied from the app can be pasted back into it, but not into other
+/// apps. This is the default clipboard, and the one used in tests.
+#[derive(Clone, Debug, Default)]
+pub struct LocalClipboard {
+    text: Option<String>,
+}
+
+impl Clipboard for LocalClipboard {
+    fn set_text(&mut self, text: String) {
+        self.text = Some(text);
+    }
+
+    fn get_text(&mut self) -> Option<String> {
+        self.text.clone()
+    }
+}
+
+/// A clipboard written through the terminal with OSC 52 escape sequences.
+///
+/// The terminal emulator puts the text in the system clipboard, which also
+/// works over SSH since only the terminal output is involved. Terminals that
+/// don't support OSC 52 ignore it.
+///
+/// Most terminals refuse to let apps read the system clipboard, so reading
+/// returns the last text copied from this app. Text copied elsewhere can
+/// still be pasted with the terminal's own paste shortcut, which arrives as a
+/// [`TextEvent::Paste`](crate::TextEvent::Paste).
+pub struct Osc52Clipboard<W: Write> {
+    writer: W,
+    local: LocalClipboard,
+}
+
+impl<W: Write> Osc52Clipboard<W> {
+    /// Create a clipboard writing escape sequences to `writer`, usually the
+    /// terminal's standard output.
+    pub fn new(writer: W) -> Self {
+        Osc52Clipboard {
+            writer,
+            local: LocalClipboard::default(),
+        }
+    }
+}
+
+impl<W: Write> Clipboard for Osc52Clipboard<W> {
+    fn set_text(&mut self, text: String) {
+        let sequence = format!("\x1b]52;c;{}\x07", base64(text.as_bytes()));
+        let result = self
+            .writer
+            .write_all(sequence.as_bytes())
+            .and_then(|()| self.writer.flush());
+        if let Err(err) = result {
+            tracing::warn!("Failed to write to the terminal clipboard: {err}");
+        }
+        self.local.set_text(text);
+    }
+
+    fn get_text(&mut self) -> Option<String> {
+        self.local.get_text()
+    }
+}
+
+/// Encodes `bytes` in standard base64, with padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | u32::from(byte) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (group >> (18 - 6 * i)) & 0x3f;
+                encoded.push(char::from(ALPHABET[index as usize]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn osc52_writes_base64_text() {
+        assert_eq!(base64(b"hi"), "aGk=");
+        assert_eq!(base64(b"hey"), "aGV5");
+        assert_eq!(base64("héllo".as_bytes()), "aMOpbGxv");
+
+        let mut clipboard = Osc52Clipboard::new(Vec::new());
+        clipboard.set_text("hi".into());
+        assert_eq!(clipboard.writer, b"\x1b]52;c;aGk=\x07");
+        assert_eq!(clipboard.get_text().as_deref(), Some("hi"));
+    }
+}
@@ -12,12 +12,15 @@ use tracing::{trace, warn};
 
 use crate::action::Action;
 use crate::dpi::LogicalPosition;
+use crate::ext_event::ExtEventSink;
 use crate::promise::PromiseToken;
 use crate::render_root::{RenderRootSignal, RenderRootState};
-use crate::text2::TextBrush;
+use crate::text2::{TextBrush, TextEngine};
 use crate::text_helpers::{ImeChangeSignal, TextFieldRegistration};
 use crate::widget::{CursorChange, WidgetMut, WidgetState};
-use crate::{CursorIcon, Insets, Point, Rect, Size, Widget, WidgetId, WidgetPod};
+use crate::{
+    Clipboard, CursorIcon, Insets, Point, Rect, Size, TimerToken, Widget, WidgetId, WidgetPod,
+};
 
 /// A macro for implementing methods on multiple contexts.
 ///
@@ -85,6 +88,8 @@ pub struct PaintCtx<'a> {
     pub(crate) depth: u32,
     pub(crate) debug_paint: bool,
     pub(crate) debug_widget: bool,
+    /// The origin of the widget in window coordinates.
+    pub(crate) window_origin: Point,
 }
 
 pub struct AccessCtx<'a> {
@@ -96,13 +101,19 @@ pub struct AccessCtx<'a> {
     pub(crate) scale_factor: f64,
 }
 
-pub struct WorkerCtx<'a> {
-    // TODO
-    #[allow(dead_code)]
-    pub(crate) global_state: &'a mut RenderRootState,
+/// A context passed to tasks running in the background, see
+/// [`EventCtx::run_in_background`].
+///
+/// Unlike other contexts, it can be moved to other threads.
+#[derive(Clone)]
+pub struct WorkerCtx {
+    pub(crate) ext_event_sink: ExtEventSink,
+    pub(crate) widget_id: WidgetId,
 }
 
-pub struct WorkerFn(pub Box<dyn FnOnce(WorkerCtx) + Send + 'static>);
+/// A background task, sent to the event loop with
+/// [`RenderRootSignal::SpawnWorker`] to be run on another thread.
+pub struct WorkerFn(Box<dyn FnOnce() + Send + 'static>);
 
 impl_context_method!(
     WidgetCtx<'_>,
@@ -359,6 +370,7 @@ impl_context_method!(WidgetCtx<'_>, EventCtx<'_>, LifeCycleCtx<'_>, {
     pub fn request_paint(&mut self) {
         trace!("request_paint");
         self.widget_state.needs_paint = true;
+        self.widget_state.is_damaged = true;
     }
 
     /// Request a layout pass.
@@ -374,6 +386,7 @@ impl_context_method!(WidgetCtx<'_>, EventCtx<'_>, LifeCycleCtx<'_>, {
     pub fn request_layout(&mut self) {
         trace!("request_layout");
         self.widget_state.needs_layout = true;
+        self.widget_state.is_damaged = true;
     }
 
     pub fn request_accessibility_update(&mut self) {
@@ -455,10 +468,28 @@ impl_context_method!(
         /// communicate with the main thread.
         pub fn run_in_background(
             &mut self,
-            _background_task: impl FnOnce(WorkerCtx) + Send + 'static,
+            background_task: impl FnOnce(WorkerCtx) + Send + 'static,
         ) {
-            // TODO - Use RenderRootSignal::SpawnWorker
-            todo!("run_in_background");
+            trace!("run_in_background");
+            let worker_ctx = WorkerCtx {
+                ext_event_sink: self.global_state.ext_event_queue.make_sink(),
+                widget_id: self.widget_state.id,
+            };
+            self.global_state
+                .signal_queue
+                .push_back(RenderRootSignal::SpawnWorker(WorkerFn::new(move || {
+                    background_task(worker_ctx);
+                })));
+        }
+
+        /// A handle that other threads can use to send actions, promise results
+        /// and callbacks to the app.
+        ///
+        /// Promise results sent through it are received by this widget as
+        /// [`LifeCycle::PromiseResult`](crate::LifeCycle::PromiseResult) events
+        /// when resolved with [`ExtEventSink::resolve_promise`].
+        pub fn ext_event_sink(&self) -> ExtEventSink {
+            self.global_state.ext_event_queue.make_sink()
         }
 
         /// Run the provided function in the background, and send its result once it's done.
@@ -466,29 +497,35 @@ impl_context_method!(
         /// The function takes a [`WorkerCtx`] which it can use to
         /// communicate with the main thread.
         ///
-        /// Once the function returns, an [`Event::PromiseResult`](crate::Event::PromiseResult)
-        /// is emitted with the return value.
+        /// Once the function returns, this widget receives a
+        /// [`LifeCycle::PromiseResult`](crate::LifeCycle::PromiseResult) with
+        /// the return value, which can be read with the returned token.
         pub fn compute_in_background<T: Any + Send>(
             &mut self,
-            _background_task: impl FnOnce(WorkerCtx) -> T + Send + 'static,
+            background_task: impl FnOnce(WorkerCtx) -> T + Send + 'static,
         ) -> PromiseToken<T> {
-            // TODO - Use RenderRootSignal::SpawnWorker
-            todo!("compute_in_background");
+            let token = PromiseToken::new();
+            self.run_in_background(move |worker_ctx| {
+                let value = background_task(worker_ctx.clone());
+                worker_ctx.resolve(token, value);
+            });
+            token
         }
 
         /// Request a timer event.
         ///
-        /// The return value is a token, which can be used to associate the
-        /// request with the event.
-        pub fn request_timer(&mut self, _deadline: Duration) -> TimerToken {
-            todo!("request_timer");
+        /// Once `deadline` has passed, this widget receives a
+        /// [`LifeCycle::Timer`](crate::LifeCycle::Timer) with the returned
+        /// token, which can be used to associate the request with the event.
+        pub fn request_timer(&mut self, deadline: Duration) -> TimerToken {
+            trace!("request_timer deadline={:?}", deadline);
+            self.global_state
+                .timers
+                .request(self.widget_state.id, deadline)
         }
     }
 );
 
-// FIXME - Remove
-pub struct TimerToken;
-
 impl EventCtx<'_> {
     /// Send a signal to parent widgets to scroll this widget into view.
     pub fn request_pan_to_this(&mut self) {
@@ -516,6 +553,22 @@ impl EventCtx<'_> {
         self.is_handled
     }
 
+    /// Ask to close the app.
+    ///
+    /// The app driver can refuse, see
+    /// [`AppDriver::on_close_requested`](crate::app_driver::AppDriver::on_close_requested).
+    pub fn request_quit(&mut self) {
+        trace!("request_quit");
+        self.global_state
+            .signal_queue
+            .push_back(RenderRootSignal::Quit);
+    }
+
+    /// The clipboard of the window.
+    pub fn clipboard(&mut self) -> &mut dyn Clipboard {
+        &mut *self.global_state.clipboard
+    }
+
     /// Request keyboard focus.
     ///
     /// Because only one widget can be focused at a time, multiple focus requests
@@ -663,6 +716,11 @@ impl LayoutCtx<'_> {
 }
 
 impl_context_method!(LayoutCtx<'_>, PaintCtx<'_>, {
+    /// The engine text widgets should lay out their text with.
+    pub fn text_engine(&self) -> TextEngine {
+        self.global_state.text_engine
+    }
+
     /// Get the contexts needed to build and paint text sections.
     pub fn text_contexts(&mut self) -> (&mut FontContext, &mut LayoutContext<TextBrush>) {
         (
@@ -709,3 +767,43 @@ impl AccessCtx<'_> {
         self.widget_state.needs_accessibility_update
     }
 }
+
+impl WorkerCtx {
+    /// The id of the widget that started the task.
+    pub fn widget_id(&self) -> WidgetId {
+        self.widget_id
+    }
+
+    /// Send `value` to the widget that started the task, which receives it as
+    /// a [`LifeCycle::PromiseResult`](crate::LifeCycle::PromiseResult) for
+    /// `token`.
+    ///
+    /// The value is dropped if the app has closed in the meantime.
+    pub fn resolve<T: Any + Send>(&self, token: PromiseToken<T>, value: T) {
+        let result = token.make_result(value);
+        if self
+            .ext_event_sink
+            .resolve_promise(result, self.widget_id)
+            .is_err()
+        {
+            warn!("App closed before promise {:?} was resolved", token);
+        }
+    }
+
+    /// A handle that can be kept to send more results to the app, from any
+    /// thread.
+    pub fn ext_event_sink(&self) -> ExtEventSink {
+        self.ext_event_sink.clone()
+    }
+}
+
+impl WorkerFn {
+    pub fn new(task: impl FnOnce() + Send + 'static) -> Self {
+        WorkerFn(Box::new(task))
+    }
+
+    /// Run the task on the current thread.
+    pub fn run(self) {
+        (self.0)();
+    }
+}
@@ -6,11 +6,12 @@
 use crate::dpi::{LogicalPosition, PhysicalPosition, PhysicalSize};
 // TODO - See issue https://github.com/linebender/xilem/issues/367
 use crate::kurbo::Rect;
+use crate::promise::PromiseResult;
 use crate::terminal::{
     event::{KeyEvent, Modifiers},
     keyboard::ModifiersState,
 };
-use crate::WidgetId;
+use crate::{TimerToken, WidgetId};
 
 use std::{collections::HashSet, path::PathBuf};
 
@@ -25,6 +26,7 @@ use std::{collections::HashSet, path::PathBuf};
 #[derive(Debug, Clone)]
 pub enum WindowEvent {
     Rescale(f64),
+    /// The window was resized. The size is in cells, see [`CellMetrics`](crate::CellMetrics).
     Resize(PhysicalSize<u32>),
     AnimFrame,
     RebuildAccessTree,
@@ -67,7 +69,6 @@ pub enum PointerEvent {
     HoverFileCancel(PointerState),
 }
 
-// TODO - Clipboard Paste?
 // TODO skip is_synthetic=true events
 #[derive(Debug, Clone)]
 pub enum TextEvent {
@@ -75,6 +76,11 @@ pub enum TextEvent {
     ModifierChange(ModifiersState),
     // TODO - Document difference with Lifecycle focus change
     FocusChange(bool),
+    /// Text pasted by the user, in one piece.
+    ///
+    /// Terminals with bracketed paste send this instead of a key event for
+    /// each character of the text.
+    Paste(String),
 }
 
 #[derive(Debug, Clone)]
@@ -143,6 +149,21 @@ pub enum LifeCycle {
     /// the monitor's refresh, causing lag or jerky animations.
     AnimFrame(u64),
 
+    /// Called when a timer requested with
+    /// [`request_timer`](crate::EventCtx::request_timer) expires.
+    ///
+    /// This is only sent to the widget that requested the timer.
+    Timer(TimerToken),
+
+    /// Called when a background task started with
+    /// [`compute_in_background`](crate::EventCtx::compute_in_background)
+    /// returned, or resolved a promise through its
+    /// [`WorkerCtx`](crate::WorkerCtx).
+    ///
+    /// This is only sent to the widget that started the task. The value can be
+    /// read with [`PromiseResult::try_get`].
+    PromiseResult(PromiseResult),
+
     // TODO - Put in StatusChange
     /// Called when the Disabled state of the widgets is changed.
     ///
@@ -199,6 +220,23 @@ pub enum InternalLifeCycle {
     /// Used to route the `DisabledChanged` event to the required widgets.
     RouteDisabledChanged,
 
+    /// Used to route the `Timer` event to the widget that requested it.
+    RouteTimer {
+        /// the widget that requested the timer
+        target: WidgetId,
+        /// the timer that expired
+        token: TimerToken,
+    },
+
+    /// Used to route the `PromiseResult` event to the widget that started the
+    /// background task.
+    RoutePromiseResult {
+        /// the widget that started the task
+        target: WidgetId,
+        /// the value returned by the task
+        result: PromiseResult,
+    },
+
     /// The parents widget origin in window coordinate space has changed.
     ParentWindowOrigin {
         mouse_pos: Option<LogicalPosition<f64>>,
@@ -281,6 +319,7 @@ impl TextEvent {
             TextEvent::KeyboardKey(_, _) => "KeyboardKey",
             TextEvent::ModifierChange(_) => "ModifierChange",
             TextEvent::FocusChange(_) => "FocusChange",
+            TextEvent::Paste(_) => "Paste",
         }
     }
 
@@ -290,6 +329,7 @@ impl TextEvent {
             // Basically every mouse click/scroll event seems to produce a modifier change event.
             TextEvent::ModifierChange(_) => true,
             TextEvent::FocusChange(_) => false,
+            TextEvent::Paste(_) => false,
         }
     }
 }
@@ -355,6 +395,8 @@ impl LifeCycle {
             LifeCycle::Internal(internal) => internal.should_propagate_to_hidden(),
             LifeCycle::WidgetAdded => true,
             LifeCycle::AnimFrame(_) => true,
+            LifeCycle::Timer(_) => true,
+            LifeCycle::PromiseResult(_) => true,
             LifeCycle::DisabledChanged(_) => true,
             LifeCycle::BuildFocusChain => false,
             LifeCycle::RequestPanToChild(_) => false,
@@ -370,10 +412,14 @@ impl LifeCycle {
                 InternalLifeCycle::RouteWidgetAdded => "RouteWidgetAdded",
                 InternalLifeCycle::RouteFocusChanged { .. } => "RouteFocusChanged",
                 InternalLifeCycle::RouteDisabledChanged => "RouteDisabledChanged",
+                InternalLifeCycle::RouteTimer { .. } => "RouteTimer",
+                InternalLifeCycle::RoutePromiseResult { .. } => "RoutePromiseResult",
                 InternalLifeCycle::ParentWindowOrigin { .. } => "ParentWindowOrigin",
             },
             LifeCycle::WidgetAdded => "WidgetAdded",
             LifeCycle::AnimFrame(_) => "AnimFrame",
+            LifeCycle::Timer(_) => "Timer",
+            LifeCycle::PromiseResult(_) => "PromiseResult",
             LifeCycle::DisabledChanged(_) => "DisabledChanged",
             LifeCycle::BuildFocusChain => "BuildFocusChain",
             LifeCycle::RequestPanToChild(_) => "RequestPanToChild",
@@ -394,7 +440,9 @@ impl InternalLifeCycle {
         match self {
             InternalLifeCycle::RouteWidgetAdded
             | InternalLifeCycle::RouteFocusChanged { .. }
-            | InternalLifeCycle::RouteDisabledChanged => true,
+            | InternalLifeCycle::RouteDisabledChanged
+            | InternalLifeCycle::RouteTimer { .. }
+            | InternalLifeCycle::RoutePromiseResult { .. } => true,
             InternalLifeCycle::ParentWindowOrigin { .. } => false,
         }
     }
@@ -1,37 +1,599 @@
 use crate::{
-    app_driver::AppDriver,
-    render_root::{RenderRoot, WindowSizePolicy},
-    PointerState, TextEvent, Widget, WindowEvent,
+    app_driver::{AppDriver, DriverCtx},
+    app_launcher::{AppLauncher, LaunchError, LaunchOptions, Tracing},
+    render_root::{RenderRoot, RenderRootSignal, WindowSizePolicy},
+    terminal::backend::{InputSender, TerminalBackend, TerminalModes},
+    terminal::signals::ProcessSignal,
+    text2::TextEngine,
+    vello::{peniko::Color, ColorDepth, RenderParams, Renderer, SubCellMode},
+    worker_pool::WorkerPool,
+    CellMetrics, CursorIcon, PointerButton, PointerEvent, PointerState, TextEvent, Widget,
+    WindowEvent,
 };
 
-use std::io::stdout;
+use std::sync::mpsc::{channel, Receiver, RecvError, RecvTimeoutError};
+use std::time::{Duration, Instant};
 
-use dpi::PhysicalSize;
+use dpi::{LogicalPosition, PhysicalPosition, PhysicalSize};
 use ratatui::{
-    backend::CrosstermBackend,
+    backend::ClearType,
+    buffer::Buffer,
     crossterm::{
         self,
-        event::{KeyCode, KeyEventKind},
-        terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-        ExecutableCommand,
+        event::{KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind},
     },
-    style::Stylize,
-    widgets::Paragraph,
-    Terminal,
+    layout::Rect,
 };
 use smol_str::SmolStr;
 
-type EventLoopError = std::io::Error;
+/// The part of the terminal an app is drawn into.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Viewport {
+    /// The whole terminal, on the alternate screen, whose previous content is
+    /// shown again once the app exits.
+    #[default]
+    Fullscreen,
+    /// The given number of lines below the cursor, leaving the rest of the
+    /// terminal untouched.
+    ///
+    /// With [`WindowSizePolicy::Content`], the viewport only has as many of
+    /// these lines as the root widget needs. The last frame stays in the
+    /// terminal's scrollback once the app exits.
+    Inline(u16),
+}
+
+/// The number of lines scrolled by one notch of the mouse wheel.
+const WHEEL_SCROLL_LINES: f64 = 3.0;
+
+/// The longest delay between two presses counted as a double click.
+const MULTI_CLICK_DELAY: Duration = Duration::from_millis(500);
+
+/// Something waking the event loop up.
+pub(crate) enum LoopMessage {
+    Terminal(crossterm::event::Event),
+    /// Reading from the terminal failed, and the input stopped.
+    InputError(std::io::Error),
+    /// Something was submitted through an [`ExtEventSink`](crate::ExtEventSink).
+    ExtEvent,
+    Signal(ProcessSignal),
+}
 
-struct MainState {
+struct MainState<B: TerminalBackend> {
     render_root: RenderRoot,
     pointer_state: PointerState,
-    #[allow(dead_code)] // TODO: remove me
+    clicks: ClickCounter,
     app_driver: Box<dyn AppDriver>,
-    terminal: Terminal<CrosstermBackend<std::io::Stdout>>,
+    workers: WorkerPool,
+    backend: B,
+    modes: TerminalModes,
+    viewport: Viewport,
+    /// The area of the terminal the app is drawn into.
+    viewport_area: Rect,
+    size_policy: WindowSizePolicy,
+    /// The number of lines of an inline viewport, before it is clamped to the
+    /// height of the terminal.
+    inline_height: u16,
+    renderer: Renderer,
+    /// The last frame sent to the terminal, which damaged areas are redrawn
+    /// into.
+    screen: Buffer,
+    color_depth: ColorDepth,
+    sub_cell_mode: SubCellMode,
+    background_color: Color,
+    /// Set when the render root asked to be redrawn.
+    needs_redraw: bool,
+    /// Set when a widget asked for an animation frame.
+    needs_anim_frame: bool,
+    /// The shortest time between two animation frames.
+    frame_interval: Duration,
+    last_anim_frame: Option<Instant>,
+    /// When [`AppDriver::on_tick`] should be called next, if the driver asked
+    /// for ticks.
+    next_tick: Option<Instant>,
+    /// Whether a widget accepting text input has focus.
+    ime_active: bool,
+    /// The area of the text cursor of the focused widget, in logical pixels.
+    ime_area: Option<kurbo::Rect>,
+    /// The cell the terminal cursor was last shown on.
+    drawn_ime_cursor: Option<(u16, u16)>,
+    /// The mouse pointer shape last requested from the terminal.
+    cursor_icon: CursorIcon,
+    /// Set while the terminal is restored for the process to be stopped.
+    suspended: bool,
     quit: bool,
 }
-impl MainState {
+impl<B: TerminalBackend> MainState<B> {
+    /// Take over the terminal and create the widget tree.
+    fn start(
+        mut backend: B,
+        root_widget: impl Widget,
+        app_driver: Box<dyn AppDriver>,
+        options: &LaunchOptions,
+    ) -> Result<Self, std::io::Error> {
+        // If a global tracing subscriber has already been set, we get an error
+        // which we swallow.
+        match &options.tracing {
+            Tracing::Default => {
+                let _ = crate::tracing_backend::try_init_tracing();
+            }
+            Tracing::Subscriber(dispatch) => {
+                let _ = tracing::dispatcher::set_global_default(dispatch.clone());
+            }
+            Tracing::Disabled => {}
+        }
+
+        let viewport = options.viewport;
+        let size_policy = options.size_policy;
+        let modes = TerminalModes {
+            alternate_screen: viewport == Viewport::Fullscreen,
+            mouse_capture: options.mouse_capture,
+            bracketed_paste: options.bracketed_paste,
+            focus_reporting: options.focus_reporting,
+        };
+        backend.enter(modes)?;
+
+        let cell_metrics = options.cell_metrics;
+        let mut render_root = RenderRoot::new(root_widget, size_policy, cell_metrics);
+        render_root.set_text_engine(TextEngine::Cells(cell_metrics));
+        render_root.set_clipboard(backend.clipboard());
+
+        let mut main_state = MainState {
+            render_root,
+            pointer_state: PointerState::empty(),
+            clicks: ClickCounter::default(),
+            app_driver,
+            workers: WorkerPool::new(),
+            backend,
+            modes,
+            viewport,
+            viewport_area: Rect::default(),
+            size_policy,
+            inline_height: match (viewport, size_policy) {
+                (Viewport::Inline(height), WindowSizePolicy::User) => height,
+                // Set by the first layout.
+                (Viewport::Inline(_), WindowSizePolicy::Content) | (Viewport::Fullscreen, _) => 0,
+            },
+            renderer: Renderer::new(),
+            screen: Buffer::empty(Rect::default()),
+            color_depth: options.color_depth.unwrap_or_else(ColorDepth::from_env),
+            sub_cell_mode: options.sub_cell_mode,
+            background_color: options.background_color,
+            needs_redraw: true,
+            needs_anim_frame: false,
+            frame_interval: Duration::from_secs(1) / options.max_fps.max(1),
+            last_anim_frame: None,
+            next_tick: None,
+            ime_active: false,
+            ime_area: None,
+            drawn_ime_cursor: None,
+            cursor_icon: CursorIcon::Default,
+            suspended: false,
+            quit: false,
+        };
+
+        if let Err(err) = main_state.place_viewport() {
+            let _ = main_state.backend.leave(modes);
+            return Err(err);
+        }
+        main_state.render_root.root_layout();
+        if let Some(widget_id) = options.initial_focus {
+            main_state.render_root.set_focus(Some(widget_id));
+        }
+        main_state.with_driver_ctx(|driver, ctx| driver.on_start(ctx));
+        Ok(main_state)
+    }
+
+    /// Let the driver know the event loop stopped, and restore the terminal.
+    fn finish(mut self) -> Result<B, std::io::Error> {
+        self.with_driver_ctx(|driver, ctx| driver.on_quit(ctx));
+        self.restore()
+    }
+
+    /// Restore the terminal, and give it back.
+    fn restore(mut self) -> Result<B, std::io::Error> {
+        let moved = self.move_below_viewport();
+        let left = self.backend.leave(self.modes);
+        moved.and(left)?;
+        Ok(self.backend)
+    }
+
+    // --- MARK: LOOP ---
+    /// Run the timers, external events, animation frame and rendering that
+    /// are due, and return when the loop should wake up next if no input
+    /// comes first.
+    ///
+    /// With no animation frame or timer pending, the loop sleeps until the
+    /// next input or external event.
+    fn run_due(&mut self) -> Result<Option<Instant>, std::io::Error> {
+        let now = Instant::now();
+        self.render_root.run_timers(now);
+        self.render_root.handle_ext_events();
+        self.handle_signals();
+        self.run_tick(now);
+        self.handle_signals();
+        self.run_anim_frame(now);
+        self.handle_signals();
+        if self.needs_redraw {
+            self.render()?;
+        }
+
+        Ok([
+            self.next_anim_frame(),
+            self.next_tick,
+            self.render_root.next_timer_deadline(),
+        ]
+        .into_iter()
+        .flatten()
+        .min())
+    }
+
+    fn handle_message(&mut self, message: LoopMessage) -> Result<(), LaunchError> {
+        match message {
+            LoopMessage::Terminal(event) => self.crossterm_event(event),
+            LoopMessage::InputError(err) => return Err(LaunchError::Input(err)),
+            // External events are handled at the start of the next iteration.
+            LoopMessage::ExtEvent => {}
+            LoopMessage::Signal(ProcessSignal::Terminate) => {
+                tracing::info!("Terminated by a signal");
+                self.quit = true;
+            }
+            LoopMessage::Signal(ProcessSignal::Suspend) => {
+                self.suspend().map_err(LaunchError::Output)?;
+            }
+            LoopMessage::Signal(ProcessSignal::Resume) => {
+                self.resume().map_err(LaunchError::Output)?;
+            }
+        }
+        Ok(())
+    }
+
+    // --- MARK: SUSPEND ---
+    /// Restore the terminal and stop the process, like <kbd>Ctrl</kbd>+<kbd>Z</kbd>
+    /// does in other programs, then resume once the process is continued.
+    fn suspend(&mut self) -> Result<(), std::io::Error> {
+        tracing::info!("Suspending");
+        self.move_below_viewport()?;
+        self.backend.leave(self.modes)?;
+        self.suspended = true;
+        self.backend.suspend()?;
+        self.resume()
+    }
+
+    /// Set the terminal up again after the process was stopped, and redraw
+    /// everything since the screen may have been overwritten meanwhile.
+    ///
+    /// An inline viewport is placed at the bottom of the terminal, since the
+    /// cursor position can't be queried while other threads read the input.
+    ///
+    /// Does nothing unless the app was suspended: the signal continuing the
+    /// process arrives after [`suspend`](Self::suspend) already resumed.
+    fn resume(&mut self) -> Result<(), std::io::Error> {
+        if !self.suspended {
+            return Ok(());
+        }
+        self.suspended = false;
+        self.backend.enter(self.modes)?;
+        if self.viewport == Viewport::Fullscreen {
+            self.clear_viewport()?;
+            self.needs_redraw = true;
+        } else {
+            let size = self.backend.size()?;
+            self.place_inline_viewport(size.height)?;
+        }
+        Ok(())
+    }
+
+    // --- MARK: VIEWPORT ---
+    /// Place the viewport in the terminal once the app starts.
+    ///
+    /// An inline viewport sized to the root widget is resized by the layout's
+    /// `SetSize` signal, handled with the other signals.
+    fn place_viewport(&mut self) -> Result<(), std::io::Error> {
+        match self.viewport {
+            // Terminals only report later changes, so send the initial size
+            // ourselves.
+            Viewport::Fullscreen => {
+                let area = self.viewport_area()?;
+                self.resize_root(area);
+                Ok(())
+            }
+            // The cursor position can only be queried now, before the
+            // terminal's input is read.
+            Viewport::Inline(_) => {
+                let (column, row) = self.backend.get_cursor()?;
+                let top = if column > 0 { row + 1 } else { row };
+                self.place_inline_viewport(top)
+            }
+        }
+    }
+
+    /// The area of the terminal the app is drawn into.
+    fn viewport_area(&mut self) -> Result<Rect, std::io::Error> {
+        // Only fullscreen viewports follow the size of the terminal by
+        // themselves.
+        if self.viewport == Viewport::Fullscreen {
+            let size = self.backend.size()?;
+            if size != self.viewport_area {
+                self.viewport_area = size;
+                self.clear_viewport()?;
+            }
+        }
+        Ok(self.viewport_area)
+    }
+
+    /// Clear the viewport, and forget the last frame so that the next one is
+    /// drawn in full.
+    fn clear_viewport(&mut self) -> Result<(), std::io::Error> {
+        match self.viewport {
+            Viewport::Fullscreen => self.backend.clear()?,
+            Viewport::Inline(_) => {
+                let area = self.viewport_area;
+                self.backend.set_cursor(area.x, area.y)?;
+                self.backend.clear_region(ClearType::AfterCursor)?;
+            }
+        }
+        self.screen = Buffer::empty(Rect::default());
+        Ok(())
+    }
+
+    /// Let the widgets know the viewport is now `area`.
+    ///
+    /// A root widget sized to its content ignores it.
+    fn resize_root(&mut self, area: Rect) {
+        if self.size_policy == WindowSizePolicy::Content {
+            return;
+        }
+        self.render_root
+            .handle_window_event(WindowEvent::Resize(PhysicalSize::new(
+                area.width.into(),
+                area.height.into(),
+            )));
+    }
+
+    /// Draw the inline viewport from row `top` of the terminal, scrolling the
+    /// terminal up if it doesn't fit below.
+    fn place_inline_viewport(&mut self, top: u16) -> Result<(), std::io::Error> {
+        let size = self.backend.size()?;
+        let (area, scroll) = inline_viewport_area(top, self.inline_height, size);
+        if scroll > 0 {
+            // Line feeds on the last row push the lines above into the
+            // scrollback, unlike scrolling escape sequences.
+            self.backend.set_cursor(0, size.height.saturating_sub(1))?;
+            self.backend.append_lines(scroll)?;
+        }
+        self.viewport_area = area;
+        self.clear_viewport()?;
+        self.needs_redraw = true;
+        self.resize_root(area);
+        Ok(())
+    }
+
+    /// Give the inline viewport `height` lines, once the root widget was laid
+    /// out.
+    fn set_inline_height(&mut self, height: u16) -> Result<(), std::io::Error> {
+        if height == self.inline_height {
+            return Ok(());
+        }
+        self.inline_height = height;
+        self.place_inline_viewport(self.viewport_area.top())
+    }
+
+    /// Put the cursor on the line below an inline viewport, so that what the
+    /// shell prints next doesn't overwrite the last frame.
+    fn move_below_viewport(&mut self) -> Result<(), std::io::Error> {
+        if self.viewport == Viewport::Fullscreen {
+            return Ok(());
+        }
+        let area = self.viewport_area;
+        if area.height == 0 {
+            return Ok(());
+        }
+        self.backend.set_cursor(0, area.bottom() - 1)?;
+        self.backend.append_lines(1)?;
+        self.backend.flush()
+    }
+
+    // --- MARK: RENDER ---
+    fn render(&mut self) -> Result<(), std::io::Error> {
+        self.needs_redraw = false;
+        let (scene, _tree_update) = self.render_root.redraw();
+        // Widgets report where their text cursor is while painting.
+        self.handle_signals();
+        let damage = self.render_root.take_damage();
+        let viewport = self.viewport_area()?;
+        let ime_cursor = self.ime_cursor();
+        if damage.is_empty() && self.screen.area == viewport && ime_cursor == self.drawn_ime_cursor
+        {
+            return Ok(());
+        }
+
+        let render_params = RenderParams {
+            base_color: self.background_color,
+            cell_metrics: self.render_root.cell_metrics(),
+            color_depth: self.color_depth,
+            sub_cell_mode: self.sub_cell_mode,
+        };
+        let previous = if self.screen.area == viewport {
+            let previous = self.screen.clone();
+            self.renderer.render_damage_to_buffer(
+                &scene,
+                &mut self.screen,
+                viewport,
+                &damage,
+                &render_params,
+            );
+            previous
+        } else {
+            // The viewport was cleared when it changed.
+            self.screen = Buffer::empty(viewport);
+            self.renderer
+                .render_to_buffer(&scene, &mut self.screen, viewport, &render_params);
+            Buffer::empty(viewport)
+        };
+        // Only the cells that differ from the previous frame are sent.
+        self.backend.draw(previous.diff(&self.screen).into_iter())?;
+        // The terminal shows IME compositions at its cursor, which is hidden
+        // otherwise.
+        match ime_cursor {
+            Some((x, y)) => {
+                self.backend.show_cursor()?;
+                self.backend.set_cursor(x, y)?;
+            }
+            None => self.backend.hide_cursor()?,
+        }
+        self.backend.flush()?;
+        self.drawn_ime_cursor = ime_cursor;
+        Ok(())
+    }
+
+    /// The cell the terminal cursor should be shown on, if a widget accepting
+    /// text input has focus.
+    fn ime_cursor(&self) -> Option<(u16, u16)> {
+        let area = self.ime_area.filter(|_| self.ime_active)?;
+        Some(ime_cursor_cell(
+            area,
+            self.render_root.cell_metrics(),
+            self.viewport_area,
+        ))
+    }
+
+    // --- MARK: SIGNALS ---
+    fn handle_signals(&mut self) {
+        while let Some(signal) = self.render_root.pop_signal() {
+            match signal {
+                RenderRootSignal::RequestRedraw => {
+                    self.needs_redraw = true;
+                }
+                RenderRootSignal::RequestAnimFrame => {
+                    self.needs_anim_frame = true;
+                }
+                RenderRootSignal::Action(action, widget_id) => {
+                    self.with_driver_ctx(|driver, ctx| driver.on_action(ctx, widget_id, action));
+                }
+                RenderRootSignal::SpawnWorker(worker) => self.workers.spawn(worker),
+                RenderRootSignal::DriverCallback(callback) => {
+                    self.with_driver_ctx(|_, ctx| callback.run(ctx));
+                }
+                RenderRootSignal::StartIme => {
+                    self.ime_active = true;
+                    self.needs_redraw = true;
+                }
+                RenderRootSignal::EndIme => {
+                    self.ime_active = false;
+                    self.needs_redraw = true;
+                }
+                // Sent while painting, so `render` places the cursor in the
+                // frame being drawn.
+                RenderRootSignal::ImeMoved(position, size) => {
+                    self.ime_area = Some(kurbo::Rect::from_origin_size(
+                        (position.x, position.y),
+                        (size.width, size.height),
+                    ));
+                }
+                RenderRootSignal::SetCursor(cursor_icon) => self.set_cursor_icon(cursor_icon),
+                RenderRootSignal::SetTitle(title) => {
+                    if let Err(err) = self.backend.set_title(&title) {
+                        tracing::warn!("Failed to set the terminal title: {err}");
+                    }
+                }
+                // Terminals can't be focused or resized by the apps they run,
+                // but inline viewports follow the size of the root widget.
+                RenderRootSignal::TakeFocus => {
+                    tracing::debug!("Ignoring focus request");
+                }
+                RenderRootSignal::SetSize(size) => {
+                    if let Viewport::Inline(max_height) = self.viewport {
+                        let height = size.height.try_into().unwrap_or(u16::MAX);
+                        if let Err(err) = self.set_inline_height(height.min(max_height)) {
+                            tracing::error!("Failed to resize the viewport: {err}");
+                        }
+                    }
+                }
+                RenderRootSignal::Quit => self.request_close(),
+            }
+        }
+    }
+
+    /// Ask the terminal to show the mouse pointer as `cursor_icon`.
+    fn set_cursor_icon(&mut self, cursor_icon: CursorIcon) {
+        if self.cursor_icon == cursor_icon {
+            return;
+        }
+        self.cursor_icon = cursor_icon;
+        if let Err(err) = self.backend.set_cursor_icon(cursor_icon) {
+            tracing::warn!("Failed to set the mouse pointer shape: {err}");
+        }
+    }
+
+    // --- MARK: ANIMATION ---
+    /// The time at which the next animation frame should run, if one was
+    /// requested.
+    fn next_anim_frame(&self) -> Option<Instant> {
+        if !self.needs_anim_frame {
+            return None;
+        }
+        match self.last_anim_frame {
+            Some(last) => Some(last + self.frame_interval),
+            None => Some(Instant::now()),
+        }
+    }
+
+    /// Runs an animation frame if one is due.
+    fn run_anim_frame(&mut self, now: Instant) {
+        if self.next_anim_frame().is_some_and(|frame| frame <= now) {
+            self.needs_anim_frame = false;
+            self.last_anim_frame = Some(now);
+            self.render_root.handle_window_event(WindowEvent::AnimFrame);
+        }
+    }
+
+    // --- MARK: TICKS ---
+    /// Calls [`AppDriver::on_tick`] if a tick is due, and schedules the next
+    /// one.
+    fn run_tick(&mut self, now: Instant) {
+        let Some(interval) = self.app_driver.tick_interval() else {
+            self.next_tick = None;
+            return;
+        };
+        // Shorter intervals would keep the event loop from ever sleeping.
+        let interval = interval.max(self.frame_interval);
+        match self.next_tick {
+            Some(next_tick) if next_tick <= now => {
+                self.next_tick = Some(next_tick_after(next_tick, interval, now));
+                self.with_driver_ctx(|driver, ctx| driver.on_tick(ctx));
+            }
+            Some(_) => {}
+            None => self.next_tick = Some(now + interval),
+        }
+    }
+
+    // --- MARK: DRIVER ---
+    /// Calls `f` with the app driver and a [`DriverCtx`], and quits if the
+    /// driver asked to.
+    fn with_driver_ctx<R>(
+        &mut self,
+        f: impl FnOnce(&mut dyn AppDriver, &mut DriverCtx<'_>) -> R,
+    ) -> R {
+        let app_driver = &mut *self.app_driver;
+        let ext_event_sink = self.render_root.ext_event_sink();
+        let (result, quit_requested) = self.render_root.edit_root_widget(|root| {
+            let mut ctx = DriverCtx::new(root, ext_event_sink);
+            let result = f(app_driver, &mut ctx);
+            (result, ctx.quit_requested)
+        });
+        self.quit |= quit_requested;
+        result
+    }
+
+    /// Closes the app, unless the driver vetoes it.
+    fn request_close(&mut self) {
+        tracing::info!("Close requested");
+        if self.with_driver_ctx(|driver, ctx| driver.on_close_requested(ctx)) {
+            self.quit = true;
+        }
+    }
+
+    // --- MARK: EVENTS ---
     fn crossterm_event(&mut self, event: crossterm::event::Event) {
         tracing::info!("event_loop_runner got crossterm event: {:?}", event);
         use crossterm::event::Event;
@@ -39,41 +601,104 @@ impl MainState {
             Event::FocusGained => {
                 self.render_root
                     .handle_text_event(TextEvent::FocusChange(true));
+                self.with_driver_ctx(|driver, ctx| driver.on_focus_change(ctx, true));
             }
             Event::FocusLost => {
                 self.render_root
                     .handle_text_event(TextEvent::FocusChange(false));
+                self.with_driver_ctx(|driver, ctx| driver.on_focus_change(ctx, false));
             }
             Event::Key(event) => {
-                if event.kind == KeyEventKind::Press && event.code == KeyCode::Char('q') {
-                    tracing::info!("Got q, quitting");
-                    self.quit = true;
-                }
                 self.crossterm_key_event(event);
             }
             Event::Mouse(event) => {
-                tracing::warn!("Ignoring mouse event: {:?}", event);
+                self.crossterm_mouse_event(event);
             }
-            Event::Paste(event) => {
-                tracing::warn!("Ignoring paste event: {:?}", event);
+            Event::Paste(text) => {
+                self.render_root.handle_text_event(TextEvent::Paste(text));
             }
             Event::Resize(width, height) => {
-                let width: u32 = width.into();
-                let height: u32 = height.into();
-                let size = PhysicalSize::new(width, height);
-                self.render_root
-                    .handle_window_event(WindowEvent::Resize(size));
+                if let Err(err) = self.terminal_resized() {
+                    tracing::error!("Failed to resize the viewport: {err}");
+                }
+                let size = PhysicalSize::new(width.into(), height.into());
+                self.with_driver_ctx(|driver, ctx| driver.on_resize(ctx, size));
             }
         }
     }
 
+    fn terminal_resized(&mut self) -> Result<(), std::io::Error> {
+        match self.viewport {
+            Viewport::Fullscreen => {
+                let area = self.viewport_area()?;
+                self.resize_root(area);
+                Ok(())
+            }
+            Viewport::Inline(_) => self.place_inline_viewport(self.viewport_area.top()),
+        }
+    }
+
+    fn crossterm_mouse_event(&mut self, mut event: crossterm::event::MouseEvent) {
+        self.handle_crossterm_modifiers(event.modifiers);
+
+        // Terminals report positions on the whole screen.
+        let Ok(viewport) = self.viewport_area() else {
+            return;
+        };
+        let inside = viewport.contains(ratatui::layout::Position::new(event.column, event.row));
+        let keeps_dragging = matches!(event.kind, MouseEventKind::Drag(_) | MouseEventKind::Up(_));
+        if !inside && !keeps_dragging {
+            return;
+        }
+        event.column = event.column.saturating_sub(viewport.x);
+        event.row = event.row.saturating_sub(viewport.y);
+
+        // Terminals report the cell under the pointer, which we place at the
+        // center of that cell.
+        let metrics = self.render_root.cell_metrics();
+        let position = metrics.cell_center(event.column.into(), event.row.into());
+        self.pointer_state.physical_position =
+            PhysicalPosition::new(event.column.into(), event.row.into());
+        self.pointer_state.position = LogicalPosition::new(position.x, position.y);
+
+        let event = match event.kind {
+            MouseEventKind::Down(button) => {
+                let button = translate_mouse_button(button);
+                self.pointer_state.count =
+                    self.clicks
+                        .press(button, (event.column, event.row), Instant::now());
+                self.pointer_state.buttons.insert(button);
+                PointerEvent::PointerDown(button, self.pointer_state.clone())
+            }
+            MouseEventKind::Up(button) => {
+                let button = translate_mouse_button(button);
+                self.pointer_state.buttons.remove(&button);
+                PointerEvent::PointerUp(button, self.pointer_state.clone())
+            }
+            MouseEventKind::Drag(_) | MouseEventKind::Moved => {
+                PointerEvent::PointerMove(self.pointer_state.clone())
+            }
+            MouseEventKind::ScrollDown => self.wheel_event(0.0, metrics.height),
+            MouseEventKind::ScrollUp => self.wheel_event(0.0, -metrics.height),
+            MouseEventKind::ScrollRight => self.wheel_event(metrics.width, 0.0),
+            MouseEventKind::ScrollLeft => self.wheel_event(-metrics.width, 0.0),
+        };
+        self.render_root.handle_pointer_event(event);
+    }
+
+    /// A wheel event scrolling by [`WHEEL_SCROLL_LINES`] cells of `x` by `y`
+    /// logical pixels.
+    fn wheel_event(&self, x: f64, y: f64) -> PointerEvent {
+        let delta = LogicalPosition::new(x * WHEEL_SCROLL_LINES, y * WHEEL_SCROLL_LINES);
+        PointerEvent::MouseWheel(delta, self.pointer_state.clone())
+    }
+
     fn crossterm_key_event(&mut self, event: crossterm::event::KeyEvent) {
         self.handle_crossterm_key_event(&event);
     }
 
-    fn handle_crossterm_key_modifiers(&mut self, event: &crossterm::event::KeyEvent) {
+    fn handle_crossterm_modifiers(&mut self, crossterm_modifiers: KeyModifiers) {
         use crate::terminal::{event::Modifiers, keyboard::ModifiersState};
-        use crossterm::event::KeyModifiers;
 
         let mut modifiers = Modifiers::default();
 
@@ -86,7 +711,7 @@ impl MainState {
             // (KeyModifiers::META, no equivalent),
         ];
         for (a, b) in MAPPINGS {
-            if event.modifiers.contains(a) {
+            if crossterm_modifiers.contains(a) {
                 modifiers.state.insert(b);
             }
         }
@@ -107,7 +732,7 @@ impl MainState {
     fn handle_crossterm_key_event(&mut self, event: &crossterm::event::KeyEvent) {
         use crate::terminal::keyboard::{Key, ModifiersState, NamedKey};
 
-        self.handle_crossterm_key_modifiers(event);
+        self.handle_crossterm_modifiers(event.modifiers);
 
         // The textual representation of the keyboard event. This is set as a
         // side effect of computing the logical key.
@@ -193,16 +818,117 @@ impl MainState {
                 state,
                 repeat,
             };
-            self.render_root.handle_text_event(TextEvent::KeyboardKey(
-                event,
-                self.pointer_state.mods.state(),
-            ));
+            let mods = self.pointer_state.mods.state();
+            let handled = self
+                .render_root
+                .handle_text_event(TextEvent::KeyboardKey(event.clone(), mods));
+            let handled = handled.is_handled()
+                || !event.state.is_pressed()
+                || self
+                    .with_driver_ctx(|driver, ctx| driver.on_unhandled_key(ctx, &event, mods))
+                    .is_handled();
+            if !handled {
+                if self.app_driver.is_quit_key(&event, mods) {
+                    self.request_close();
+                } else if self.backend.can_suspend() && is_suspend_key(&event, mods) {
+                    if let Err(err) = self.suspend() {
+                        tracing::error!("Failed to suspend: {err}");
+                    }
+                }
+            }
         } else {
             tracing::warn!("Ignoring unknown key from crossterm: {:?}", event);
         }
     }
 }
 
+/// The time of the first tick after `now`, following a tick due at `due`.
+///
+/// Ticks that were missed are skipped.
+fn next_tick_after(due: Instant, interval: Duration, now: Instant) -> Instant {
+    let next = due + interval;
+    if next > now {
+        next
+    } else {
+        now + interval
+    }
+}
+
+/// The cell of the terminal at the top-left corner of the IME `area`, within
+/// the `viewport` the app is drawn into.
+fn ime_cursor_cell(area: kurbo::Rect, cell_metrics: CellMetrics, viewport: Rect) -> (u16, u16) {
+    let cell = cell_metrics.to_cells(area.origin());
+    let clamp =
+        |value: f64, len: u16| value.floor().clamp(0.0, f64::from(len.saturating_sub(1))) as u16;
+    (
+        viewport.x + clamp(cell.x, viewport.width),
+        viewport.y + clamp(cell.y, viewport.height),
+    )
+}
+
+/// The area of an inline viewport of `height` lines starting at row `top` of
+/// a terminal of `size`, and the number of lines the terminal must be scrolled
+/// up by for it to fit.
+fn inline_viewport_area(top: u16, height: u16, size: Rect) -> (Rect, u16) {
+    let height = height.min(size.height);
+    let scroll = top.saturating_add(height).saturating_sub(size.height);
+    let area = Rect::new(0, top - scroll, size.width, height);
+    (area, scroll)
+}
+
+/// Whether a key press left unhandled by the widgets should suspend the app.
+fn is_suspend_key(
+    event: &crate::terminal::event::KeyEvent,
+    mods: crate::terminal::keyboard::ModifiersState,
+) -> bool {
+    use crate::terminal::keyboard::{Key, ModifiersState};
+
+    match &event.logical_key {
+        Key::Character(chr) => mods == ModifiersState::CONTROL && chr.eq_ignore_ascii_case("z"),
+        _ => false,
+    }
+}
+
+/// Counts repeated presses of the same button on the same cell, to detect
+/// double and triple clicks.
+#[derive(Default)]
+struct ClickCounter {
+    last_press: Option<(PointerButton, (u16, u16), Instant)>,
+    count: u8,
+}
+
+impl ClickCounter {
+    /// Records a press of `button` on the cell at `cell`, and returns the
+    /// number of clicks it makes, from 1 to 3.
+    ///
+    /// A fourth click starts over at a single click.
+    fn press(&mut self, button: PointerButton, cell: (u16, u16), now: Instant) -> u8 {
+        let repeated = match self.last_press {
+            Some((last_button, last_cell, last_time)) => {
+                last_button == button
+                    && last_cell == cell
+                    && now.duration_since(last_time) <= MULTI_CLICK_DELAY
+            }
+            None => false,
+        };
+        self.count = if repeated && self.count < 3 {
+            self.count + 1
+        } else {
+            1
+        };
+        self.last_press = Some((button, cell, now));
+        self.count
+    }
+}
+
+fn translate_mouse_button(button: MouseButton) -> PointerButton {
+    match button {
+        MouseButton::Left => PointerButton::Primary,
+        MouseButton::Right => PointerButton::Secondary,
+        MouseButton::Middle => PointerButton::Auxiliary,
+    }
+}
+
 fn translate_function_key_number(n: u8) -> Option<crate::terminal::keyboard::Key> {
     use crate::terminal::keyboard::{Key, NamedKey};
 
@@ -278,89 +1004,416 @@ fn translate_media_key_code(
     }
 }
 
+/// Run the app with the default settings of [`AppLauncher`].
 pub fn run(
-    // Clearly, this API needs to be refactored, so we don't mind forcing this to be passed in here directly
-    // This is passed in mostly to allow configuring the Android app
     root_widget: impl Widget,
     app_driver: impl AppDriver + 'static,
-) -> Result<(), EventLoopError> {
-    run_with(root_widget, app_driver)
+) -> Result<(), LaunchError> {
+    AppLauncher::new().launch(root_widget, app_driver)
+}
+
+pub(crate) fn launch<B: TerminalBackend>(
+    backend: B,
+    root_widget: impl Widget,
+    app_driver: Box<dyn AppDriver>,
+    options: LaunchOptions,
+) -> Result<B, LaunchError> {
+    let mut main_state =
+        MainState::start(backend, root_widget, app_driver, &options).map_err(LaunchError::Setup)?;
+
+    let (sender, messages) = channel();
+    main_state.render_root.set_ext_event_waker({
+        let sender = sender.clone();
+        move || {
+            let _ = sender.send(LoopMessage::ExtEvent);
+        }
+    });
+
+    let input = InputSender::new(move |message| sender.send(message).is_ok());
+    let input = match main_state.backend.start_input(input) {
+        Ok(input) => input,
+        Err(err) => {
+            let _ = main_state.restore();
+            return Err(LaunchError::Setup(err));
+        }
+    };
+    let result = run_app(&mut main_state, &messages);
+    // Stop reading before the terminal is restored, so that input meant for
+    // the shell isn't consumed.
+    drop(input);
+    match result {
+        Ok(()) => main_state.finish().map_err(LaunchError::Restore),
+        Err(err) => {
+            let _ = main_state.restore();
+            Err(err)
+        }
+    }
 }
 
-pub fn run_with(
+/// Run the app in a future with the default settings of [`AppLauncher`], see
+/// [`AppLauncher::launch_async`].
+#[cfg(feature = "async")]
+pub async fn run_async(
     root_widget: impl Widget,
     app_driver: impl AppDriver + 'static,
-) -> Result<(), EventLoopError> {
-    // If there is no default tracing subscriber, we set our own. If one has
-    // already been set, we get an error which we swallow.
-    // By now, we're about to take control of the event loop. The user is unlikely
-    // to try to set their own subscriber once the event loop has started.
-    let _ = crate::tracing_backend::try_init_tracing();
-
-    stdout().execute(EnterAlternateScreen)?;
-    enable_raw_mode()?;
-    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
-    terminal.clear()?;
-
-    // TODO: We can't know this scale factor until later?
-    let scale_factor = 1.0;
-    let render_root = RenderRoot::new(root_widget, WindowSizePolicy::User, scale_factor);
-
-    let mut main_state = MainState {
-        render_root,
-        pointer_state: PointerState::empty(),
-        app_driver: Box::new(app_driver),
-        terminal,
-        quit: false,
+) -> Result<(), LaunchError> {
+    AppLauncher::new()
+        .launch_async(root_widget, app_driver)
+        .await
+}
+
+#[cfg(feature = "async")]
+pub(crate) async fn launch_async(
+    root_widget: impl Widget,
+    app_driver: Box<dyn AppDriver>,
+    options: LaunchOptions,
+) -> Result<(), LaunchError> {
+    use futures_util::{future, FutureExt, StreamExt};
+
+    let mut main_state = MainState::start(
+        crate::terminal::backend::StdoutTerminal::new(),
+        root_widget,
+        app_driver,
+        &options,
+    )
+    .map_err(LaunchError::Setup)?;
+
+    let (sender, mut messages) = futures_channel::mpsc::unbounded();
+    main_state.render_root.set_ext_event_waker({
+        let sender = sender.clone();
+        move || {
+            let _ = sender.unbounded_send(LoopMessage::ExtEvent);
+        }
+    });
+    let signal_watcher = crate::terminal::signals::SignalWatcher::spawn(move |signal| {
+        let _ = sender.unbounded_send(LoopMessage::Signal(signal));
+    });
+    let signal_watcher = match signal_watcher {
+        Ok(signal_watcher) => signal_watcher,
+        Err(err) => {
+            let _ = main_state.restore();
+            return Err(LaunchError::Setup(err));
+        }
     };
 
-    run_app(&mut main_state)?;
+    let mut terminal_events = crossterm::event::EventStream::new().fuse();
+    let result = async {
+        while !main_state.quit {
+            let wake_up = main_state.run_due().map_err(LaunchError::Output)?;
+            // Signals handled by `run_due` may have closed the app.
+            if main_state.quit {
+                break;
+            }
+            let mut timeout = match wake_up {
+                Some(wake_up) => {
+                    futures_timer::Delay::new(wake_up.saturating_duration_since(Instant::now()))
+                        .left_future()
+                }
+                None => future::pending().right_future(),
+            }
+            .fuse();
+            let message = futures_util::select! {
+                event = terminal_events.next() => match event {
+                    Some(Ok(event)) => LoopMessage::Terminal(event),
+                    Some(Err(err)) => LoopMessage::InputError(err),
+                    None => break,
+                },
+                message = messages.next() => match message {
+                    Some(message) => message,
+                    None => break,
+                },
+                () = timeout => continue,
+            };
+            main_state.handle_message(message)?;
+        }
+        Ok(())
+    }
+    .await;
+    // Stop reading before the terminal is restored, so that input meant for
+    // the shell isn't consumed.
+    drop(terminal_events);
+    drop(signal_watcher);
+    match result {
+        Ok(()) => main_state.finish().map(drop).map_err(LaunchError::Restore),
+        Err(err) => {
+            let _ = main_state.restore();
+            Err(err)
+        }
+    }
+}
+
+fn run_app<B: TerminalBackend>(
+    main_state: &mut MainState<B>,
+    messages: &Receiver<LoopMessage>,
+) -> Result<(), LaunchError> {
+    while !main_state.quit {
+        let wake_up = main_state.run_due().map_err(LaunchError::Output)?;
+        // Signals handled by `run_due` may have closed the app.
+        if main_state.quit {
+            break;
+        }
+        let message = match wake_up {
+            Some(wake_up) => {
+                match messages.recv_timeout(wake_up.saturating_duration_since(Instant::now())) {
+                    Ok(message) => message,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            None => match messages.recv() {
+                Ok(message) => message,
+                Err(RecvError) => break,
+            },
+        };
+        main_state.handle_message(message)?;
+    }
+    Ok(())
+}
 
-    stdout().execute(LeaveAlternateScreen)?;
-    disable_raw_mode()?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, WidgetId};
 
-    #[cfg(any())]
-    {
-        let render_cx = RenderContext::new();
-        // TODO: We can't know this scale factor until later?
-        let scale_factor = 1.0;
-        let mut main_state = MainState {
-            render_cx,
-            render_root: RenderRoot::new(root_widget, WindowSizePolicy::User, scale_factor),
-            renderer: None,
-            pointer_state: PointerState::empty(),
-            app_driver: Box::new(app_driver),
-            proxy: event_loop.create_proxy(),
+    #[test]
+    fn repeated_presses_count_clicks() {
+        let mut clicks = ClickCounter::default();
+        let start = Instant::now();
+        let later = |millis| start + Duration::from_millis(millis);
+        let primary = PointerButton::Primary;
+
+        assert_eq!(clicks.press(primary, (2, 3), start), 1);
+        assert_eq!(clicks.press(primary, (2, 3), later(200)), 2);
+        assert_eq!(clicks.press(primary, (2, 3), later(400)), 3);
+        assert_eq!(clicks.press(primary, (2, 3), later(600)), 1);
+        // Moving to another cell, switching buttons or waiting starts over.
+        assert_eq!(clicks.press(primary, (3, 3), later(700)), 1);
+        assert_eq!(
+            clicks.press(PointerButton::Secondary, (3, 3), later(800)),
+            1
+        );
+        assert_eq!(
+            clicks.press(PointerButton::Secondary, (3, 3), later(1500)),
+            1
+        );
+    }
 
-            window: WindowState::Uninitialized(window),
+    #[test]
+    fn ime_cursor_stays_on_screen() {
+        let metrics = CellMetrics {
+            width: 8.0,
+            height: 16.0,
         };
+        let size = Rect::new(0, 0, 10, 5);
+        let area = |x, y| kurbo::Rect::new(x, y, x + 1.0, y + 16.0);
 
-        // If there is no default tracing subscriber, we set our own. If one has
-        // already been set, we get an error which we swallow.
-        // By now, we're about to take control of the event loop. The user is unlikely
-        // to try to set their own subscriber once the event loop has started.
-        let _ = crate::tracing_backend::try_init_tracing();
+        assert_eq!(ime_cursor_cell(area(20.0, 33.0), metrics, size), (2, 2));
+        assert_eq!(ime_cursor_cell(area(-4.0, -1.0), metrics, size), (0, 0));
+        assert_eq!(ime_cursor_cell(area(500.0, 500.0), metrics, size), (9, 4));
+        // Inline viewports don't start at the top of the terminal.
+        let viewport = Rect::new(0, 20, 10, 5);
+        assert_eq!(
+            ime_cursor_cell(area(20.0, 33.0), metrics, viewport),
+            (2, 22)
+        );
+    }
 
-        event_loop.run_app(&mut main_state)
+    #[test]
+    fn inline_viewports_scroll_to_fit() {
+        let size = Rect::new(0, 0, 80, 24);
+
+        assert_eq!(
+            inline_viewport_area(5, 4, size),
+            (Rect::new(0, 5, 80, 4), 0)
+        );
+        assert_eq!(
+            inline_viewport_area(22, 4, size),
+            (Rect::new(0, 20, 80, 4), 2)
+        );
+        // Viewports higher than the terminal are clamped to it.
+        assert_eq!(
+            inline_viewport_area(10, 30, size),
+            (Rect::new(0, 0, 80, 24), 10)
+        );
     }
 
-    Ok(())
-}
+    #[test]
+    fn missed_ticks_are_skipped() {
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        let interval = Duration::from_millis(100);
 
-fn run_app(main_state: &mut MainState) -> Result<(), std::io::Error> {
-    while !main_state.quit {
-        main_state.terminal.draw(|frame| {
-            let area = frame.size();
-            frame.render_widget(
-                Paragraph::new("Hello Ratatui! (press 'q' to quit)")
-                    .white()
-                    .on_blue(),
-                area,
-            );
-        })?;
-        if crossterm::event::poll(std::time::Duration::from_millis(16))? {
-            main_state.crossterm_event(crossterm::event::read()?);
+        assert_eq!(next_tick_after(at(100), interval, at(120)), at(200));
+        assert_eq!(next_tick_after(at(100), interval, at(450)), at(550));
+    }
+
+    struct Driver;
+
+    impl AppDriver for Driver {
+        fn on_action(&mut self, _ctx: &mut DriverCtx<'_>, _widget_id: WidgetId, _action: Action) {}
+    }
+
+    #[test]
+    fn default_quit_keys_need_control() {
+        use crate::terminal::event::{ElementState, KeyEvent};
+        use crate::terminal::keyboard::{Key, ModifiersState};
+
+        let key = |chr: &str| KeyEvent {
+            logical_key: Key::Character(chr.into()),
+            text: Some(chr.into()),
+            state: ElementState::Pressed,
+            repeat: false,
+        };
+        assert!(Driver.is_quit_key(&key("c"), ModifiersState::CONTROL));
+        assert!(Driver.is_quit_key(&key("q"), ModifiersState::CONTROL));
+        assert!(!Driver.is_quit_key(&key("q"), ModifiersState::empty()));
+        assert!(!Driver.is_quit_key(&key("q"), ModifiersState::CONTROL | ModifiersState::SHIFT));
+        assert!(!Driver.is_quit_key(&key("x"), ModifiersState::CONTROL));
+    }
+
+    #[test]
+    fn suspend_key_is_control_z() {
+        use crate::terminal::event::{ElementState, KeyEvent};
+        use crate::terminal::keyboard::{Key, ModifiersState};
+
+        let key = |chr: &str| KeyEvent {
+            logical_key: Key::Character(chr.into()),
+            text: Some(chr.into()),
+            state: ElementState::Pressed,
+            repeat: false,
+        };
+        assert!(is_suspend_key(&key("z"), ModifiersState::CONTROL));
+        assert!(!is_suspend_key(&key("z"), ModifiersState::empty()));
+        assert!(!is_suspend_key(
+            &key("z"),
+            ModifiersState::CONTROL | ModifiersState::ALT
+        ));
+    }
+
+    #[test]
+    fn apps_draw_to_any_backend() {
+        use crate::widget::{Label, RootWidget};
+        use ratatui::backend::TestBackend;
+
+        struct QuitOnTick;
+
+        impl AppDriver for QuitOnTick {
+            fn on_action(
+                &mut self,
+                _ctx: &mut DriverCtx<'_>,
+                _widget_id: WidgetId,
+                _action: Action,
+            ) {
+            }
+
+            fn tick_interval(&self) -> Option<Duration> {
+                Some(Duration::from_millis(1))
+            }
+
+            fn on_tick(&mut self, ctx: &mut DriverCtx<'_>) {
+                ctx.request_quit();
+            }
         }
+
+        let backend = AppLauncher::new()
+            .without_tracing()
+            .launch_with_backend(
+                TestBackend::new(12, 2),
+                RootWidget::new(Label::new("Hello")),
+                QuitOnTick,
+            )
+            .unwrap();
+        let first_line: String = (0..12)
+            .map(|x| backend.buffer().get(x, 0).symbol())
+            .collect();
+        assert_eq!(first_line.trim_end(), "Hello");
+    }
+
+    #[test]
+    fn continuing_after_a_suspend_resumes_once() {
+        use crate::widget::{Label, RootWidget};
+        use ratatui::backend::{Backend, TestBackend};
+
+        let mut backend = TestBackend::new(6, 6);
+        backend.set_cursor(0, 2).unwrap();
+        let options = AppLauncher::new()
+            .without_tracing()
+            .viewport(Viewport::Inline(1))
+            .options;
+        let root = RootWidget::new(Label::new("Hi"));
+        let mut main_state = MainState::start(backend, root, Box::new(Driver), &options).unwrap();
+        main_state.render().unwrap();
+
+        main_state.suspend().unwrap();
+        // The signal continuing the process follows.
+        main_state
+            .handle_message(LoopMessage::Signal(ProcessSignal::Resume))
+            .unwrap();
+        main_state.render().unwrap();
+
+        // The viewport moved to the bottom once, scrolling the last frame up
+        // by a line.
+        let rows_with_text = (0..6)
+            .filter(|&y| main_state.backend.buffer().get(0, y).symbol() == "H")
+            .collect::<Vec<_>>();
+        assert_eq!(rows_with_text, [1, 5]);
+    }
+
+    #[test]
+    fn ticks_are_no_faster_than_frames() {
+        use crate::widget::{Label, RootWidget};
+        use ratatui::backend::TestBackend;
+
+        struct TickAlways;
+
+        impl AppDriver for TickAlways {
+            fn on_action(
+                &mut self,
+                _ctx: &mut DriverCtx<'_>,
+                _widget_id: WidgetId,
+                _action: Action,
+            ) {
+            }
+
+            fn tick_interval(&self) -> Option<Duration> {
+                Some(Duration::ZERO)
+            }
+        }
+
+        let options = AppLauncher::new().without_tracing().max_fps(10).options;
+        let root = RootWidget::new(Label::new("Hi"));
+        let mut main_state =
+            MainState::start(TestBackend::new(6, 1), root, Box::new(TickAlways), &options).unwrap();
+        let start = Instant::now();
+        let wake_up = main_state.run_due().unwrap().unwrap();
+        assert!(wake_up >= start + Duration::from_millis(100));
+    }
+
+    #[test]
+    fn text_cursor_moves_with_the_frame() {
+        use crate::widget::{RootWidget, Textbox, WidgetPod};
+        use crossterm::event::{Event, KeyEvent};
+        use ratatui::backend::{Backend, TestBackend};
+
+        let textbox = WidgetId::next();
+        let options = AppLauncher::new()
+            .without_tracing()
+            .initial_focus(textbox)
+            .options;
+        let root = RootWidget::from_pod(WidgetPod::new_with_id(Textbox::new(""), textbox));
+        let mut main_state =
+            MainState::start(TestBackend::new(20, 3), root, Box::new(Driver), &options).unwrap();
+        main_state.run_due().unwrap();
+        let start = main_state.backend.get_cursor().unwrap();
+
+        let key = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE);
+        main_state
+            .handle_message(LoopMessage::Terminal(Event::Key(key)))
+            .unwrap();
+        main_state.run_due().unwrap();
+        assert_eq!(
+            main_state.backend.get_cursor().unwrap(),
+            (start.0 + 1, start.1)
+        );
     }
-    Ok(())
 }
@@ -2,22 +2,28 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Simple handle for submitting external events.
-#![allow(unused)]
 
-use std::any::Any;
 use std::collections::VecDeque;
-use std::sync::{Arc, Mutex};
-
-use druid_shell::IdleHandle;
+use std::sync::{Arc, Mutex, Weak};
 
+use crate::app_driver::{DriverCallback, DriverCtx};
 use crate::promise::PromiseResult;
 use crate::widget::WidgetId;
-
-// FIXME - Remove
-pub struct WindowId;
+use crate::Action;
 
 pub(crate) enum ExtMessage {
-    Promise(PromiseResult, WidgetId, WindowId),
+    Promise(PromiseResult, WidgetId),
+    Action(Action, WidgetId),
+    Callback(DriverCallback),
+}
+
+type Waker = Box<dyn Fn() + Send + Sync>;
+
+#[derive(Default)]
+struct Shared {
+    queue: Mutex<VecDeque<ExtMessage>>,
+    /// Called after each submitted item, to wake the event loop up.
+    waker: Mutex<Option<Waker>>,
 }
 
 /// A thing that can move into other threads and be used to submit commands back
@@ -26,23 +32,15 @@ pub(crate) enum ExtMessage {
 /// This API is preliminary, and may be changed or removed without warning.
 #[derive(Clone)]
 pub struct ExtEventSink {
-    queue: Arc<Mutex<VecDeque<ExtMessage>>>,
-    handle: Arc<Mutex<Option<IdleHandle>>>,
+    shared: Weak<Shared>,
 }
 
 /// The stuff that we hold onto inside the app that is related to the
 /// handling of external events.
 #[derive(Default)]
 pub(crate) struct ExtEventQueue {
-    /// A shared queue of items that have been sent to us.
-    queue: Arc<Mutex<VecDeque<ExtMessage>>>,
-    /// This doesn't exist when the app starts and it can go away if a window closes, so we keep a
-    /// reference here and can update it when needed. Note that this reference is shared with all
-    /// `ExtEventSink`s, so that we can update them too.
-    handle: Arc<Mutex<Option<IdleHandle>>>,
-    /// The window that the handle belongs to, so we can keep track of when
-    /// we need to get a new handle.
-    pub(crate) handle_window_id: Option<WindowId>,
+    /// Sinks only hold a weak reference, so that they fail once the app is gone.
+    shared: Arc<Shared>,
 }
 
 /// An error that occurs if an external event cannot be submitted.
@@ -57,40 +55,61 @@ impl ExtEventQueue {
 
     pub(crate) fn make_sink(&self) -> ExtEventSink {
         ExtEventSink {
-            queue: self.queue.clone(),
-            handle: self.handle.clone(),
+            shared: Arc::downgrade(&self.shared),
         }
     }
 
-    pub(crate) fn set_idle(&mut self, handle: IdleHandle, window_id: WindowId) {
-        self.handle.lock().unwrap().replace(handle);
-        self.handle_window_id = Some(window_id);
-    }
-
-    pub(crate) fn has_pending_items(&self) -> bool {
-        !self.queue.lock().unwrap().is_empty()
+    /// Set the function called, from any thread, each time a sink submits an
+    /// item.
+    pub(crate) fn set_waker(&mut self, waker: impl Fn() + Send + Sync + 'static) {
+        *self.shared.waker.lock().unwrap() = Some(Box::new(waker));
     }
 
     pub(crate) fn recv(&mut self) -> Option<ExtMessage> {
-        self.queue.lock().unwrap().pop_front()
+        self.shared.queue.lock().unwrap().pop_front()
     }
 }
 
 impl ExtEventSink {
-    #[allow(missing_docs)]
+    /// Send `result` to `target_widget`, which receives it as a
+    /// [`LifeCycle::PromiseResult`](crate::LifeCycle::PromiseResult).
     pub fn resolve_promise(
         &self,
         result: PromiseResult,
         target_widget: WidgetId,
-        target_window: WindowId,
     ) -> Result<(), ExtEventError> {
-        if let Some(handle) = self.handle.lock().unwrap().as_mut() {
-            todo!()
-        }
-        self.queue
+        self.submit(ExtMessage::Promise(result, target_widget))
+    }
+
+    /// Submit `action` as if `source_widget` had submitted it, so that it
+    /// reaches [`AppDriver::on_action`](crate::app_driver::AppDriver::on_action).
+    pub fn submit_action(
+        &self,
+        action: Action,
+        source_widget: WidgetId,
+    ) -> Result<(), ExtEventError> {
+        self.submit(ExtMessage::Action(action, source_widget))
+    }
+
+    /// Run `callback` on the event loop's thread, with a [`DriverCtx`] giving
+    /// access to the widget tree.
+    pub fn run_on_driver(
+        &self,
+        callback: impl FnOnce(&mut DriverCtx<'_>) + Send + 'static,
+    ) -> Result<(), ExtEventError> {
+        self.submit(ExtMessage::Callback(DriverCallback::new(callback)))
+    }
+
+    fn submit(&self, message: ExtMessage) -> Result<(), ExtEventError> {
+        let shared = self.shared.upgrade().ok_or(ExtEventError)?;
+        shared
+            .queue
             .lock()
             .map_err(|_| ExtEventError)?
-            .push_back(ExtMessage::Promise(result, target_widget, target_window));
+            .push_back(message);
+        if let Some(waker) = shared.waker.lock().map_err(|_| ExtEventError)?.as_ref() {
+            waker();
+        }
         Ok(())
     }
 }
@@ -102,3 +121,44 @@ impl std::fmt::Display for ExtEventError {
 }
 
 impl std::error::Error for ExtEventError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    #[test]
+    fn sinks_wake_the_event_loop_from_other_threads() {
+        let wake_count = Arc::new(AtomicUsize::new(0));
+        let mut queue = ExtEventQueue::new();
+        queue.set_waker({
+            let wake_count = wake_count.clone();
+            move || {
+                wake_count.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        let sink = queue.make_sink();
+        thread::spawn(move || {
+            let id = WidgetId::next();
+            sink.submit_action(Action::CheckboxChecked(true), id)
+                .unwrap();
+            sink.run_on_driver(|_| {}).unwrap();
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(wake_count.load(Ordering::SeqCst), 2);
+        assert!(matches!(
+            queue.recv(),
+            Some(ExtMessage::Action(Action::CheckboxChecked(true), _))
+        ));
+        assert!(matches!(queue.recv(), Some(ExtMessage::Callback(_))));
+        assert!(queue.recv().is_none());
+
+        let sink = queue.make_sink();
+        drop(queue);
+        assert!(sink.run_on_driver(|_| {}).is_err());
+    }
+}
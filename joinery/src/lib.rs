@@ -91,17 +91,20 @@ pub use cursor_icon::{CursorIcon, ParseError as CursorIconParseError};
 pub use dpi;
 pub use kurbo;
 
-// TODO: hack impersonate various things
-pub mod vello;
-
 #[macro_use]
 mod util;
 
+// TODO: hack impersonate various things
+pub mod vello;
+
 mod action;
 mod bloom;
 mod box_constraints;
+mod cell_metrics;
+mod clipboard;
 mod contexts;
 mod event;
+mod ext_event;
 pub mod paint_scene_helpers;
 pub mod promise;
 pub mod render_root;
@@ -109,10 +112,13 @@ pub mod terminal;
 pub mod testing;
 pub mod text_helpers;
 pub mod theme;
+mod timers;
 pub mod widget;
+mod worker_pool;
 
 // TODO
 pub mod app_driver;
+pub mod app_launcher;
 pub mod debug_logger;
 pub mod debug_values;
 pub mod event_loop_runner;
@@ -121,14 +127,20 @@ mod tracing_backend;
 
 pub use action::Action;
 pub use box_constraints::BoxConstraints;
-pub use contexts::{AccessCtx, EventCtx, LayoutCtx, LifeCycleCtx, PaintCtx, WidgetCtx};
+pub use cell_metrics::CellMetrics;
+pub use clipboard::{Clipboard, LocalClipboard, Osc52Clipboard};
+pub use contexts::{
+    AccessCtx, EventCtx, LayoutCtx, LifeCycleCtx, PaintCtx, WidgetCtx, WorkerCtx, WorkerFn,
+};
 pub use event::{
     AccessEvent, InternalLifeCycle, LifeCycle, PointerButton, PointerEvent, PointerState,
     StatusChange, TextEvent, WindowEvent, WindowTheme,
 };
+pub use ext_event::{ExtEventError, ExtEventSink};
 pub use kurbo::{Affine, Insets, Point, Rect, Size, Vec2};
 pub use peniko::{Color, Gradient};
 pub use util::{AsAny, Handled};
 pub use widget::{BackgroundBrush, Widget, WidgetId, WidgetPod, WidgetState};
 
 pub use text_helpers::ArcStr;
+pub use timers::TimerToken;
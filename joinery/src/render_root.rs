@@ -13,25 +13,31 @@ use tracing::{debug, info_span, warn};
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
 
+use crate::app_driver::DriverCallback;
 use crate::contexts::{EventCtx, LayoutCtx, LifeCycleCtx, PaintCtx, WidgetCtx, WorkerFn};
 use crate::debug_logger::DebugLogger;
 use crate::dpi::{LogicalPosition, LogicalSize, PhysicalSize};
 use crate::event::{PointerEvent, TextEvent, WindowEvent};
-use crate::kurbo::Point;
-use crate::text2::TextBrush;
+use crate::ext_event::{ExtEventQueue, ExtEventSink, ExtMessage};
+use crate::kurbo::{Point, Rect};
+use crate::text2::{TextBrush, TextEngine};
+use crate::timers::TimerQueue;
 use crate::widget::{WidgetMut, WidgetState};
 use crate::{
-    AccessCtx, AccessEvent, Action, BoxConstraints, CursorIcon, Handled, InternalLifeCycle,
-    LifeCycle, Widget, WidgetId, WidgetPod,
+    AccessCtx, AccessEvent, Action, BoxConstraints, CellMetrics, Clipboard, CursorIcon, Handled,
+    InternalLifeCycle, LifeCycle, LocalClipboard, Widget, WidgetId, WidgetPod,
 };
 
 // TODO - Remove pub(crate)
 pub struct RenderRoot {
     pub(crate) root: WidgetPod<Box<dyn Widget>>,
     pub(crate) size_policy: WindowSizePolicy,
+    /// The size of the window, in cells.
     pub(crate) size: PhysicalSize<u32>,
+    /// Maps cells to the logical pixels used by kurbo coordinates.
+    pub(crate) cell_metrics: CellMetrics,
     // TODO - Currently this is always 1.0
-    // kurbo coordinates are assumed to be in logical pixels
+    // Only used for accessibility bounds.
     pub(crate) scale_factor: f64,
     /// Is `Some` if the most recently displayed frame was an animation frame.
     pub(crate) last_anim: Option<Instant>,
@@ -50,6 +56,13 @@ pub(crate) struct RenderRootState {
     pub(crate) next_focused_widget: Option<WidgetId>,
     pub(crate) font_context: FontContext,
     pub(crate) text_layout_context: LayoutContext<TextBrush>,
+    pub(crate) text_engine: TextEngine,
+    /// The areas of the window that changed since the last call to
+    /// [`RenderRoot::take_damage`], in logical pixels.
+    pub(crate) damage: Vec<Rect>,
+    pub(crate) clipboard: Box<dyn Clipboard>,
+    pub(crate) timers: TimerQueue,
+    pub(crate) ext_event_queue: ExtEventQueue,
 }
 
 /// Defines how a windows size should be determined
@@ -75,20 +88,33 @@ pub enum RenderRootSignal {
     ImeMoved(LogicalPosition<f64>, LogicalSize<f64>),
     RequestRedraw,
     RequestAnimFrame,
+    /// A widget started a background task, which should be run on another
+    /// thread.
     SpawnWorker(WorkerFn),
+    /// Another thread submitted a callback with
+    /// [`ExtEventSink::run_on_driver`], which should be run with a
+    /// [`DriverCtx`](crate::app_driver::DriverCtx).
+    DriverCallback(DriverCallback),
     TakeFocus,
     SetCursor(CursorIcon),
     SetSize(PhysicalSize<u32>),
     SetTitle(String),
+    /// A widget asked to close the app, see [`EventCtx::request_quit`].
+    Quit,
 }
 
 impl RenderRoot {
-    pub fn new(root_widget: impl Widget, size_policy: WindowSizePolicy, scale_factor: f64) -> Self {
+    pub fn new(
+        root_widget: impl Widget,
+        size_policy: WindowSizePolicy,
+        cell_metrics: CellMetrics,
+    ) -> Self {
         let mut root = RenderRoot {
             root: WidgetPod::new(root_widget).boxed(),
             size_policy,
             size: PhysicalSize::new(0, 0),
-            scale_factor,
+            cell_metrics,
+            scale_factor: 1.0,
             last_anim: None,
             last_mouse_pos: None,
             cursor_icon: CursorIcon::Default,
@@ -99,6 +125,11 @@ impl RenderRoot {
                 next_focused_widget: None,
                 font_context: FontContext::default(),
                 text_layout_context: LayoutContext::new(),
+                text_engine: TextEngine::default(),
+                damage: Vec::new(),
+                clipboard: Box::new(LocalClipboard::default()),
+                timers: TimerQueue::new(),
+                ext_event_queue: ExtEventQueue::new(),
             },
             rebuild_access_tree: true,
         };
@@ -169,6 +200,65 @@ impl RenderRoot {
         self.root_on_text_event(event)
     }
 
+    /// The time at which the next timer expires, if any.
+    ///
+    /// The event loop should call [`run_timers`](Self::run_timers) then.
+    pub fn next_timer_deadline(&self) -> Option<Instant> {
+        self.state.timers.next_deadline()
+    }
+
+    /// Send a [`LifeCycle::Timer`] to widgets whose timers expired by `now`.
+    pub fn run_timers(&mut self, now: Instant) {
+        for (token, target) in self.state.timers.advance(now) {
+            self.root_lifecycle(LifeCycle::Internal(InternalLifeCycle::RouteTimer {
+                target,
+                token,
+            }));
+        }
+    }
+
+    /// A handle that other threads can use to send actions, promise results
+    /// and callbacks to the app.
+    pub fn ext_event_sink(&self) -> ExtEventSink {
+        self.state.ext_event_queue.make_sink()
+    }
+
+    /// Set the function called, from the submitting thread, each time an
+    /// [`ExtEventSink`] submits something.
+    ///
+    /// The event loop should wake up and call
+    /// [`handle_ext_events`](Self::handle_ext_events) then.
+    pub fn set_ext_event_waker(&mut self, waker: impl Fn() + Send + Sync + 'static) {
+        self.state.ext_event_queue.set_waker(waker);
+    }
+
+    /// Handle the items submitted through [`ExtEventSink`]s.
+    ///
+    /// Promise results are sent to their widget as
+    /// [`LifeCycle::PromiseResult`] events, while actions and callbacks are
+    /// queued as signals for the event loop.
+    pub fn handle_ext_events(&mut self) {
+        while let Some(message) = self.state.ext_event_queue.recv() {
+            match message {
+                ExtMessage::Promise(result, target) => {
+                    self.root_lifecycle(LifeCycle::Internal(
+                        InternalLifeCycle::RoutePromiseResult { target, result },
+                    ));
+                }
+                ExtMessage::Action(action, widget_id) => {
+                    self.state
+                        .signal_queue
+                        .push_back(RenderRootSignal::Action(action, widget_id));
+                }
+                ExtMessage::Callback(callback) => {
+                    self.state
+                        .signal_queue
+                        .push_back(RenderRootSignal::DriverCallback(callback));
+                }
+            }
+        }
+    }
+
     pub fn redraw(&mut self) -> (Scene, TreeUpdate) {
         // TODO - Xilem's reconciliation logic will have to be called
         // by the function that calls this
@@ -185,10 +275,49 @@ impl RenderRoot {
                 .push_back(RenderRootSignal::RequestRedraw);
         }
 
-        // TODO - Improve caching of scenes.
         (self.root_paint(), self.root_accessibility())
     }
 
+    /// Returns the areas of the window that changed since the last call, in
+    /// logical pixels.
+    ///
+    /// Damage is recorded by [`redraw`](Self::redraw): a widget damages the
+    /// area it covers when it requests a paint or layout pass, and both its old
+    /// and new areas when it moves or is resized. Everything outside of these
+    /// areas is painted exactly as in the previous frame.
+    pub fn take_damage(&mut self) -> Vec<Rect> {
+        std::mem::take(&mut self.state.damage)
+    }
+
+    /// Set the engine text widgets lay out their text with.
+    ///
+    /// This defaults to [`TextEngine::Parley`].
+    pub fn set_text_engine(&mut self, text_engine: TextEngine) {
+        self.state.text_engine = text_engine;
+        self.root.state.needs_layout = true;
+        self.state.damage.push(self.get_kurbo_size().to_rect());
+        self.state
+            .signal_queue
+            .push_back(RenderRootSignal::RequestRedraw);
+    }
+
+    /// Set the clipboard widgets copy to and paste from.
+    ///
+    /// This defaults to a [`LocalClipboard`].
+    pub fn set_clipboard(&mut self, clipboard: impl Clipboard + 'static) {
+        self.state.clipboard = Box::new(clipboard);
+    }
+
+    /// The clipboard widgets copy to and paste from.
+    pub fn clipboard(&mut self) -> &mut dyn Clipboard {
+        &mut *self.state.clipboard
+    }
+
+    /// The size of a cell, used to convert cell positions to logical pixels.
+    pub fn cell_metrics(&self) -> CellMetrics {
+        self.cell_metrics
+    }
+
     pub fn pop_signal(&mut self) -> Option<RenderRootSignal> {
         self.state.signal_queue.pop_front()
     }
@@ -205,6 +334,14 @@ impl RenderRoot {
         self.cursor_icon
     }
 
+    /// Give keyboard focus to the widget with the given id, or to no widget.
+    pub fn set_focus(&mut self, target: Option<WidgetId>) {
+        let mut widget_state =
+            WidgetState::new(self.root.id(), Some(self.get_kurbo_size()), "<root>");
+        self.state.next_focused_widget = target;
+        self.post_event_processing(&mut widget_state);
+    }
+
     // --- MARK: EDIT ROOT---
     pub fn edit_root_widget<R>(
         &mut self,
@@ -437,7 +574,7 @@ impl RenderRoot {
         layout_ctx.global_state.debug_logger.pop_span();
 
         if let WindowSizePolicy::Content = self.size_policy {
-            let new_size = LogicalSize::new(size.width, size.height).to_physical(self.scale_factor);
+            let new_size = self.cell_metrics.cells_for_size(size);
             if self.size != new_size {
                 self.size = new_size;
                 layout_ctx
@@ -462,6 +599,7 @@ impl RenderRoot {
             depth: 0,
             debug_paint: false,
             debug_widget: false,
+            window_origin: Point::ORIGIN,
         };
 
         let mut scene = Scene::new();
@@ -526,8 +664,7 @@ impl RenderRoot {
     }
 
     fn get_kurbo_size(&self) -> kurbo::Size {
-        let size = self.size.to_logical(self.scale_factor);
-        kurbo::Size::new(size.width, size.height)
+        self.cell_metrics.logical_size(self.size)
     }
 
     // --- MARK: POST-EVENT ---
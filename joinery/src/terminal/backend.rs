@@ -0,0 +1,419 @@
+//! The terminals an app can be drawn to and read input from.
+//!
+//! Apps run in the terminal of the process by default, through
+//! [`StdoutTerminal`]. Other terminals, such as a PTY, a socket or a
+//! [`TestBackend`] in tests, can be used through
+//! [`AppLauncher::launch_with_backend`](crate::app_launcher::AppLauncher::launch_with_backend)
+//! by implementing [`TerminalBackend`].
+
+use std::io::{self, stdout, Stdout, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use ratatui::backend::{Backend, ClearType, CrosstermBackend, TestBackend, WindowSize};
+use ratatui::buffer::Cell;
+use ratatui::crossterm::{
+    self,
+    event::{
+        DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+        EnableFocusChange, EnableMouseCapture, Event,
+    },
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, SetTitle},
+    ExecutableCommand,
+};
+use ratatui::layout::Rect;
+
+use crate::event_loop_runner::LoopMessage;
+use crate::terminal::guard::TerminalGuard;
+use crate::terminal::signals::{stop_process, ProcessSignal, SignalWatcher};
+use crate::{Clipboard, CursorIcon, LocalClipboard, Osc52Clipboard};
+
+/// How often the input thread checks whether the event loop has stopped.
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The features of the terminal enabled while the app runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TerminalModes {
+    /// Draw on the alternate screen, whose previous content is shown again
+    /// once the app exits.
+    pub alternate_screen: bool,
+    /// Report mouse events.
+    pub mouse_capture: bool,
+    /// Report pasted text as a single [`Event::Paste`].
+    pub bracketed_paste: bool,
+    /// Report when the terminal gains or loses focus.
+    pub focus_reporting: bool,
+}
+
+/// A terminal the event loop draws frames to and reads input from.
+///
+/// Frames are drawn through the methods of ratatui's [`Backend`], which the
+/// event loop calls with the cells that changed since the previous frame.
+/// Input is sent to the event loop as crossterm [`Event`]s, whatever the
+/// terminal library reading it.
+///
+/// Besides [`start_input`](Self::start_input), every method has a default
+/// doing nothing, for terminals without the corresponding feature.
+pub trait TerminalBackend: Backend {
+    /// Keeps reading input while alive, see [`start_input`](Self::start_input).
+    type Input;
+
+    /// Set the terminal up for the app, for instance by enabling raw mode and
+    /// the given `modes`.
+    ///
+    /// This is also called when the app resumes after being
+    /// [suspended](Self::suspend).
+    fn enter(&mut self, modes: TerminalModes) -> io::Result<()> {
+        let _ = modes;
+        Ok(())
+    }
+
+    /// Put the terminal back in its normal state, undoing
+    /// [`enter`](Self::enter) with the same `modes`.
+    fn leave(&mut self, modes: TerminalModes) -> io::Result<()> {
+        let _ = modes;
+        Ok(())
+    }
+
+    /// Start sending the terminal's input to `input`, from any thread, until
+    /// the returned value is dropped.
+    ///
+    /// This is called once the app is set up, and the returned value is
+    /// dropped before [`leave`](Self::leave), so that input meant for the
+    /// shell isn't consumed.
+    fn start_input(&mut self, input: InputSender) -> io::Result<Self::Input>;
+
+    /// Set the title of the terminal window.
+    fn set_title(&mut self, title: &str) -> io::Result<()> {
+        let _ = title;
+        Ok(())
+    }
+
+    /// Show the mouse pointer as `cursor_icon`.
+    fn set_cursor_icon(&mut self, cursor_icon: CursorIcon) -> io::Result<()> {
+        let _ = cursor_icon;
+        Ok(())
+    }
+
+    /// The clipboard widgets copy to and paste from.
+    ///
+    /// This defaults to a [`LocalClipboard`].
+    fn clipboard(&mut self) -> Box<dyn Clipboard> {
+        Box::new(LocalClipboard::default())
+    }
+
+    /// Whether the app can be [suspended](Self::suspend) with
+    /// <kbd>Ctrl</kbd>+<kbd>Z</kbd>.
+    fn can_suspend(&self) -> bool {
+        false
+    }
+
+    /// Stop the process, and return once it is continued.
+    ///
+    /// The terminal was [left](Self::leave) beforehand, and is entered again
+    /// afterwards.
+    fn suspend(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Sends a terminal's input to the event loop of an app.
+///
+/// See [`TerminalBackend::start_input`].
+#[derive(Clone)]
+pub struct InputSender {
+    send: Arc<dyn Fn(LoopMessage) -> bool + Send + Sync>,
+}
+
+impl InputSender {
+    pub(crate) fn new(send: impl Fn(LoopMessage) -> bool + Send + Sync + 'static) -> Self {
+        InputSender {
+            send: Arc::new(send),
+        }
+    }
+
+    /// Send an event to the app.
+    ///
+    /// Returns `false` once the app stopped, after which input can't be
+    /// received anymore.
+    pub fn send(&self, event: Event) -> bool {
+        (self.send)(LoopMessage::Terminal(event))
+    }
+
+    /// Stop the app with a [`LaunchError::Input`](crate::app_launcher::LaunchError::Input),
+    /// since reading the terminal failed.
+    pub fn send_error(&self, error: io::Error) -> bool {
+        (self.send)(LoopMessage::InputError(error))
+    }
+
+    pub(crate) fn send_signal(&self, signal: ProcessSignal) -> bool {
+        (self.send)(LoopMessage::Signal(signal))
+    }
+}
+
+// --- MARK: STDOUT ---
+/// The terminal the process runs in, drawn to through its standard output.
+///
+/// This is the terminal used by [`AppLauncher::launch`](crate::app_launcher::AppLauncher::launch).
+/// It also handles the signals the process receives from the terminal, so
+/// that the app can be suspended and resumed.
+pub struct StdoutTerminal {
+    backend: CrosstermBackend<Stdout>,
+    guard: Option<TerminalGuard>,
+}
+
+/// Reads the input of a [`StdoutTerminal`], and the signals sent to the
+/// process.
+pub struct StdoutInput {
+    _input_thread: InputThread,
+    _signal_watcher: SignalWatcher,
+}
+
+impl StdoutTerminal {
+    /// The terminal of the process, which is set up once the app is launched.
+    pub fn new() -> Self {
+        StdoutTerminal {
+            backend: CrosstermBackend::new(stdout()),
+            guard: None,
+        }
+    }
+}
+
+impl Default for StdoutTerminal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Backend for StdoutTerminal {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        self.backend.draw(content)
+    }
+
+    fn append_lines(&mut self, n: u16) -> io::Result<()> {
+        self.backend.append_lines(n)
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.backend.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.backend.show_cursor()
+    }
+
+    fn get_cursor(&mut self) -> io::Result<(u16, u16)> {
+        self.backend.get_cursor()
+    }
+
+    fn set_cursor(&mut self, x: u16, y: u16) -> io::Result<()> {
+        self.backend.set_cursor(x, y)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.backend.clear()
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
+        self.backend.clear_region(clear_type)
+    }
+
+    fn size(&self) -> io::Result<Rect> {
+        self.backend.size()
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        self.backend.window_size()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Backend::flush(&mut self.backend)
+    }
+}
+
+impl TerminalBackend for StdoutTerminal {
+    type Input = StdoutInput;
+
+    fn enter(&mut self, modes: TerminalModes) -> io::Result<()> {
+        match &mut self.guard {
+            Some(guard) => guard.enter(),
+            None => {
+                self.guard = Some(TerminalGuard::new(modes)?);
+                Ok(())
+            }
+        }
+    }
+
+    fn leave(&mut self, _modes: TerminalModes) -> io::Result<()> {
+        // The guard knows what it enabled.
+        match &mut self.guard {
+            Some(guard) => guard.leave(),
+            None => Ok(()),
+        }
+    }
+
+    fn start_input(&mut self, input: InputSender) -> io::Result<StdoutInput> {
+        let signal_watcher = SignalWatcher::spawn({
+            let input = input.clone();
+            move |signal| {
+                input.send_signal(signal);
+            }
+        })?;
+        Ok(StdoutInput {
+            _input_thread: InputThread::spawn(input)?,
+            _signal_watcher: signal_watcher,
+        })
+    }
+
+    fn set_title(&mut self, title: &str) -> io::Result<()> {
+        self.backend.set_title(title)
+    }
+
+    fn set_cursor_icon(&mut self, cursor_icon: CursorIcon) -> io::Result<()> {
+        self.backend.set_cursor_icon(cursor_icon)
+    }
+
+    fn clipboard(&mut self) -> Box<dyn Clipboard> {
+        Box::new(Osc52Clipboard::new(stdout()))
+    }
+
+    fn can_suspend(&self) -> bool {
+        cfg!(unix)
+    }
+
+    fn suspend(&mut self) -> io::Result<()> {
+        stop_process()
+    }
+}
+
+// --- MARK: WRITERS ---
+/// Draws to anything implementing [`Write`], such as a PTY or a socket, with
+/// the escape sequences of the terminal on the other end.
+///
+/// Raw mode is the business of whoever owns that terminal, and no input is
+/// read: wrap the backend in your own [`TerminalBackend`] to send the input
+/// through [`start_input`](TerminalBackend::start_input).
+impl<W: Write> TerminalBackend for CrosstermBackend<W> {
+    type Input = ();
+
+    fn enter(&mut self, modes: TerminalModes) -> io::Result<()> {
+        if modes.alternate_screen {
+            self.execute(EnterAlternateScreen)?;
+        }
+        if modes.mouse_capture {
+            self.execute(EnableMouseCapture)?;
+        }
+        if modes.bracketed_paste {
+            self.execute(EnableBracketedPaste)?;
+        }
+        if modes.focus_reporting {
+            self.execute(EnableFocusChange)?;
+        }
+        Ok(())
+    }
+
+    fn leave(&mut self, modes: TerminalModes) -> io::Result<()> {
+        if modes.focus_reporting {
+            self.execute(DisableFocusChange)?;
+        }
+        if modes.bracketed_paste {
+            self.execute(DisableBracketedPaste)?;
+        }
+        if modes.mouse_capture {
+            self.execute(DisableMouseCapture)?;
+        }
+        if modes.alternate_screen {
+            self.execute(LeaveAlternateScreen)?;
+        }
+        self.show_cursor()
+    }
+
+    fn start_input(&mut self, _input: InputSender) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_title(&mut self, title: &str) -> io::Result<()> {
+        self.execute(SetTitle(title)).map(drop)
+    }
+
+    /// Sends an OSC 22 escape sequence, which terminals that don't support it
+    /// ignore.
+    fn set_cursor_icon(&mut self, cursor_icon: CursorIcon) -> io::Result<()> {
+        write!(self, "\x1b]22;{}\x1b\\", cursor_icon.name())?;
+        Write::flush(self)
+    }
+}
+
+/// Draws into the buffer of the [`TestBackend`], which tests can then
+/// inspect. No input is read.
+impl TerminalBackend for TestBackend {
+    type Input = ();
+
+    fn start_input(&mut self, _input: InputSender) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// --- MARK: INPUT THREAD ---
+/// A thread reading the input of the process's terminal, and sending it to
+/// the event loop.
+///
+/// Since the event loop waits on a channel rather than on the terminal, other
+/// threads can wake it up by sending messages on the same channel.
+struct InputThread {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl InputThread {
+    fn spawn(input: InputSender) -> Result<Self, std::io::Error> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = thread::Builder::new().name("joinery-input".into()).spawn({
+            let stop = stop.clone();
+            move || read_input(&input, &stop)
+        })?;
+        Ok(InputThread {
+            stop,
+            handle: Some(handle),
+        })
+    }
+}
+
+impl Drop for InputThread {
+    fn drop(&mut self) {
+        // Stop reading before the terminal is restored, so that input meant
+        // for the shell isn't consumed.
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn read_input(input: &InputSender, stop: &AtomicBool) {
+    while !stop.load(Ordering::Relaxed) {
+        // Polling with a timeout instead of blocking on `read` lets the thread
+        // notice when it should stop.
+        let event = match crossterm::event::poll(INPUT_POLL_INTERVAL) {
+            Ok(false) => continue,
+            Ok(true) => crossterm::event::read(),
+            Err(err) => Err(err),
+        };
+        let sent = match event {
+            Ok(event) => input.send(event),
+            Err(err) => {
+                input.send_error(err);
+                return;
+            }
+        };
+        if !sent {
+            return;
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Putting the terminal back in its normal state when the app exits, panics or
+//! is suspended.
+
+use std::io::stdout;
+use std::panic;
+use std::sync::{Mutex, Once, PoisonError};
+use std::thread::{self, ThreadId};
+
+use ratatui::crossterm::{
+    cursor::Show,
+    event::{
+        DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+        EnableFocusChange, EnableMouseCapture,
+    },
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand,
+};
+
+use crate::terminal::backend::TerminalModes;
+
+/// How the terminal was set up, while it is set up.
+static TERMINAL_OWNER: Mutex<Option<Owner>> = Mutex::new(None);
+
+struct Owner {
+    /// The thread which set the terminal up.
+    thread: ThreadId,
+    modes: TerminalModes,
+}
+
+/// Sets the terminal up for the app: raw mode, and the given
+/// [`TerminalModes`].
+///
+/// The terminal is restored when the guard is dropped, including while
+/// unwinding from a panic. A panic hook also restores it before the panic
+/// message is printed, so that the message isn't lost with the alternate
+/// screen.
+pub(crate) struct TerminalGuard {
+    modes: TerminalModes,
+}
+
+impl TerminalGuard {
+    pub(crate) fn new(modes: TerminalModes) -> Result<Self, std::io::Error> {
+        install_panic_hook();
+        if let Err(err) = enter(modes) {
+            let _ = leave();
+            return Err(err);
+        }
+        Ok(TerminalGuard { modes })
+    }
+
+    /// Set the terminal up again after [`leave`](Self::leave), or after
+    /// something else reset it while the process was stopped.
+    ///
+    /// Does nothing if the terminal is already set up.
+    pub(crate) fn enter(&mut self) -> Result<(), std::io::Error> {
+        enter(self.modes)
+    }
+
+    /// Restore the terminal.
+    ///
+    /// Does nothing if the terminal was already restored.
+    pub(crate) fn leave(&mut self) -> Result<(), std::io::Error> {
+        leave()
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        if let Err(err) = leave() {
+            tracing::error!("Failed to restore the terminal: {err}");
+        }
+    }
+}
+
+fn enter(modes: TerminalModes) -> Result<(), std::io::Error> {
+    let mut owner = TERMINAL_OWNER
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if owner.is_some() {
+        return Ok(());
+    }
+    // Set before the first change, so that a partial setup is still undone.
+    *owner = Some(Owner {
+        thread: thread::current().id(),
+        modes,
+    });
+    if modes.alternate_screen {
+        stdout().execute(EnterAlternateScreen)?;
+    }
+    if modes.mouse_capture {
+        stdout().execute(EnableMouseCapture)?;
+    }
+    if modes.bracketed_paste {
+        stdout().execute(EnableBracketedPaste)?;
+    }
+    if modes.focus_reporting {
+        stdout().execute(EnableFocusChange)?;
+    }
+    enable_raw_mode()
+}
+
+fn leave() -> Result<(), std::io::Error> {
+    let mut owner = TERMINAL_OWNER
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    let Some(Owner { modes, .. }) = owner.take() else {
+        return Ok(());
+    };
+    // Undo as much as possible, even if a step fails. Only what was enabled is
+    // disabled: leaving the alternate screen without being on it moves the
+    // cursor on some terminals.
+    let mut results = Vec::new();
+    if modes.focus_reporting {
+        results.push(stdout().execute(DisableFocusChange).map(drop));
+    }
+    if modes.bracketed_paste {
+        results.push(stdout().execute(DisableBracketedPaste).map(drop));
+    }
+    if modes.mouse_capture {
+        results.push(stdout().execute(DisableMouseCapture).map(drop));
+    }
+    if modes.alternate_screen {
+        results.push(stdout().execute(LeaveAlternateScreen).map(drop));
+    }
+    results.push(stdout().execute(Show).map(drop));
+    results.push(disable_raw_mode());
+    results.into_iter().collect()
+}
+
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            // Panics on other threads, such as background tasks, may be caught
+            // while the app keeps running.
+            let panicked_on_owner = TERMINAL_OWNER.try_lock().is_ok_and(|owner| {
+                owner
+                    .as_ref()
+                    .is_some_and(|owner| owner.thread == thread::current().id())
+            });
+            if panicked_on_owner {
+                let _ = leave();
+            }
+            previous_hook(info);
+        }));
+    });
+}
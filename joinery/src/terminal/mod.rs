@@ -1,2 +1,5 @@
+pub mod backend;
 pub mod event;
+pub(crate) mod guard;
 pub mod keyboard;
+pub(crate) mod signals;
@@ -0,0 +1,82 @@
+//! Handling the signals the process receives from the terminal and the
+//! system.
+
+use std::thread::JoinHandle;
+
+/// A signal the event loop should act on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ProcessSignal {
+    /// The process was asked to stop, or the terminal was closed.
+    Terminate,
+    /// The process was asked to suspend itself, and should restore the
+    /// terminal first.
+    Suspend,
+    /// The process was continued after being stopped.
+    Resume,
+}
+
+/// A thread waiting for signals, and passing them to a callback.
+///
+/// On platforms without Unix signals, this does nothing.
+pub(crate) struct SignalWatcher {
+    #[cfg(unix)]
+    handle: signal_hook::iterator::Handle,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SignalWatcher {
+    #[cfg(unix)]
+    pub(crate) fn spawn(
+        on_signal: impl Fn(ProcessSignal) + Send + 'static,
+    ) -> Result<Self, std::io::Error> {
+        use signal_hook::consts::{SIGCONT, SIGHUP, SIGINT, SIGTERM, SIGTSTP};
+        use signal_hook::iterator::Signals;
+
+        let mut signals = Signals::new([SIGTERM, SIGHUP, SIGINT, SIGTSTP, SIGCONT])?;
+        let handle = signals.handle();
+        let thread = std::thread::Builder::new()
+            .name("joinery-signals".into())
+            .spawn(move || {
+                for signal in signals.forever() {
+                    on_signal(match signal {
+                        SIGTSTP => ProcessSignal::Suspend,
+                        SIGCONT => ProcessSignal::Resume,
+                        _ => ProcessSignal::Terminate,
+                    });
+                }
+            })?;
+        Ok(SignalWatcher {
+            handle,
+            thread: Some(thread),
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn spawn(
+        _on_signal: impl Fn(ProcessSignal) + Send + 'static,
+    ) -> Result<Self, std::io::Error> {
+        Ok(SignalWatcher { thread: None })
+    }
+}
+
+impl Drop for SignalWatcher {
+    fn drop(&mut self) {
+        // Unregisters the signals, so that they get their default behavior
+        // back.
+        #[cfg(unix)]
+        self.handle.close();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Stop the process the way the terminal would on <kbd>Ctrl</kbd>+<kbd>Z</kbd>,
+/// returning once it is continued.
+///
+/// Does nothing on platforms without job control.
+pub(crate) fn stop_process() -> Result<(), std::io::Error> {
+    #[cfg(unix)]
+    signal_hook::low_level::emulate_default_handler(signal_hook::consts::SIGTSTP)?;
+    Ok(())
+}
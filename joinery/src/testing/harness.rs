@@ -3,15 +3,21 @@
 
 //! Tools and infrastructure for testing widgets.
 
+use std::time::{Duration, Instant};
+
 use super::screenshots::get_image_diff;
 use super::snapshot_utils::get_cargo_workspace;
 use crate::action::Action;
+use crate::app_driver::DriverCtx;
 use crate::dpi::{LogicalPosition, PhysicalPosition, PhysicalSize};
 use crate::event::{PointerButton, PointerEvent, PointerState, TextEvent, WindowEvent};
 use crate::render_root::{RenderRoot, RenderRootSignal, WindowSizePolicy};
+use crate::text2::TextEngine;
 use crate::tracing_backend::try_init_tracing;
 use crate::widget::{WidgetMut, WidgetRef};
-use crate::{Color, Handled, Point, Size, Vec2, Widget, WidgetId};
+use crate::{
+    CellMetrics, Clipboard, Color, ExtEventSink, Handled, Point, Rect, Size, Vec2, Widget, WidgetId,
+};
 use image::io::Reader as ImageReader;
 use image::{Rgba, RgbaImage};
 
@@ -55,7 +61,9 @@ pub const HARNESS_DEFAULT_BACKGROUND_COLOR: Color = Color::rgb8(0x29, 0x29, 0x29
 /// The passage of time is simulated with the [`move_timers_forward`](Self::move_timers_forward) methods. **(TODO -
 /// Doesn't move animations forward.)**
 ///
-/// **(TODO - ExtEvents aren't handled.)**
+/// Background tasks started with [`EventCtx::run_in_background`](crate::EventCtx::run_in_background)
+/// run on the test thread right after the event which started them, and their results are
+/// delivered before the event method returns.
 ///
 /// **(TODO - Painting invalidation might not be accurate.)**
 ///
@@ -112,6 +120,8 @@ pub struct TestHarness {
     render_root: RenderRoot,
     mouse_state: PointerState,
     window_size: PhysicalSize<u32>,
+    /// The simulated current time, see [`move_timers_forward`](Self::move_timers_forward).
+    time: Instant,
     #[allow(dead_code)] // FIXME
     background_color: Color,
 }
@@ -174,11 +184,18 @@ impl TestHarness {
         let _ = try_init_tracing();
 
         let mut harness = TestHarness {
-            render_root: RenderRoot::new(root_widget, WindowSizePolicy::User, 1.0),
+            render_root: RenderRoot::new(root_widget, WindowSizePolicy::User, CellMetrics::UNIT),
             mouse_state,
             window_size,
+            time: Instant::now(),
             background_color,
         };
+        // Timers only expire when the test moves time forward.
+        harness
+            .render_root
+            .state
+            .timers
+            .use_virtual_time(harness.time);
         harness.process_window_event(WindowEvent::Resize(window_size));
 
         harness
@@ -220,7 +237,73 @@ impl TestHarness {
         handled
     }
 
+    /// Paint the widget tree, and return the areas of the window that changed
+    /// since the last call, in logical pixels.
+    ///
+    /// See [`RenderRoot::take_damage`].
+    pub fn redraw_damage(&mut self) -> Vec<Rect> {
+        let _ = self.render_root.redraw();
+        self.render_root.take_damage()
+    }
+
+    /// Set the engine text widgets lay out their text with.
+    ///
+    /// Unlike the default engine, [`TextEngine::Cells`] doesn't depend on the
+    /// fonts installed on the machine running the tests.
+    pub fn set_text_engine(&mut self, text_engine: TextEngine) {
+        self.render_root.set_text_engine(text_engine);
+        self.process_state_after_event();
+    }
+
+    /// Set the clipboard widgets copy to and paste from.
+    ///
+    /// This defaults to an empty [`LocalClipboard`](crate::LocalClipboard).
+    pub fn set_clipboard(&mut self, clipboard: impl Clipboard + 'static) {
+        self.render_root.set_clipboard(clipboard);
+    }
+
+    /// The clipboard widgets copy to and paste from.
+    pub fn clipboard(&mut self) -> &mut dyn Clipboard {
+        self.render_root.clipboard()
+    }
+
+    /// A handle that other threads can use to send actions, promise results
+    /// and callbacks to the widgets.
+    ///
+    /// What they send is only handled after the next event, or by
+    /// [`process_ext_events`](Self::process_ext_events).
+    pub fn ext_event_sink(&self) -> ExtEventSink {
+        self.render_root.ext_event_sink()
+    }
+
+    /// Handle what was sent through [`ext_event_sink`](Self::ext_event_sink)
+    /// so far.
+    ///
+    /// Submitted actions can then be read with [`pop_action`](Self::pop_action).
+    pub fn process_ext_events(&mut self) {
+        self.process_state_after_event();
+    }
+
     fn process_state_after_event(&mut self) {
+        self.render_root.handle_ext_events();
+        while let Some(signal) = self.render_root.pop_signal_matching(|signal| {
+            matches!(
+                signal,
+                RenderRootSignal::SpawnWorker(_) | RenderRootSignal::DriverCallback(_)
+            )
+        }) {
+            match signal {
+                RenderRootSignal::SpawnWorker(worker) => worker.run(),
+                RenderRootSignal::DriverCallback(callback) => {
+                    let ext_event_sink = self.render_root.ext_event_sink();
+                    self.render_root.edit_root_widget(|root| {
+                        callback.run(&mut DriverCtx::new(root, ext_event_sink));
+                    });
+                }
+                _ => unreachable!(),
+            }
+            self.render_root.handle_ext_events();
+        }
         if self.root_widget().state().needs_layout {
             self.render_root.root_layout();
         }
@@ -369,7 +452,6 @@ impl TestHarness {
         todo!("write the loop above");
     }
 
-    #[cfg(FALSE)]
     /// Simulate the passage of time.
     ///
     /// If you create any timer in a widget, this method is the only way to trigger
@@ -379,16 +461,9 @@ impl TestHarness {
     /// **(TODO - Doesn't move animations forward.)**
     pub fn move_timers_forward(&mut self, duration: Duration) {
         // TODO - handle animations
-        let tokens = self
-            .mock_app
-            .window
-            .mock_timer_queue
-            .as_mut()
-            .unwrap()
-            .move_forward(duration);
-        for token in tokens {
-            self.process_event(Event::Timer(token));
-        }
+        self.time += duration;
+        self.render_root.run_timers(self.time);
+        self.process_state_after_event();
     }
 
     // --- MARK: GETTERS ---
@@ -421,6 +496,14 @@ impl TestHarness {
             .find_widget_by_id(self.render_root.state.focused_widget?)
     }
 
+    /// Give keyboard focus to the widget with the given id, or to no widget.
+    ///
+    /// See [`RenderRoot::set_focus`].
+    pub fn set_focus(&mut self, target: Option<WidgetId>) {
+        self.render_root.set_focus(target);
+        self.process_state_after_event();
+    }
+
     /// Call the provided visitor on every widget in the widget tree.
     pub fn inspect_widgets(&mut self, f: impl Fn(WidgetRef<'_, dyn Widget>) + 'static) {
         fn inspect(
@@ -0,0 +1,519 @@
+//! Text layout measured in terminal cells.
+
+use std::ops::Range;
+
+use kurbo::{Affine, Point, Rect};
+use parley::layout::{Alignment, Cursor};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+use xi_unicode::LineBreakIterator;
+
+use crate::vello::peniko::Fill;
+use crate::vello::{Scene, TextSpan};
+use crate::CellMetrics;
+
+use super::TextBrush;
+
+/// The number of columns between two tab stops.
+const TAB_WIDTH: u32 = 8;
+
+/// A layout where every grapheme cluster occupies as many cells as its
+/// unicode width.
+///
+/// This is what a terminal displays, so unlike a layout shaped with real fonts
+/// it needs no font data, and measurements are exact multiples of the cell
+/// size. Lines are wrapped at the opportunities given by the unicode line
+/// breaking algorithm.
+///
+/// Baselines are at the bottom of their row.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CellLayout {
+    metrics: CellMetrics,
+    clusters: Vec<CellCluster>,
+    lines: Vec<CellLine>,
+}
+
+/// A grapheme cluster.
+#[derive(Clone, Debug)]
+struct CellCluster {
+    text_range: Range<usize>,
+    /// The first column of the cluster, from the start of its line.
+    col: u32,
+    /// The number of columns the cluster occupies.
+    width: u32,
+    is_whitespace: bool,
+}
+
+#[derive(Clone, Debug)]
+struct CellLine {
+    /// Indices into [`CellLayout::clusters`].
+    clusters: Range<usize>,
+    text_range: Range<usize>,
+    /// Columns before the start of the line, from the alignment.
+    offset: u32,
+    /// The width of the line, excluding trailing whitespace.
+    width: u32,
+    /// The width of the line, including trailing whitespace.
+    full_width: u32,
+}
+
+impl CellLayout {
+    /// Lay out `text`, wrapping lines wider than `max_advance` logical pixels.
+    pub(crate) fn build(
+        &mut self,
+        text: &str,
+        metrics: CellMetrics,
+        max_advance: Option<f32>,
+        alignment: Alignment,
+    ) {
+        self.metrics = metrics;
+        self.clusters.clear();
+        self.lines.clear();
+
+        let max_cols =
+            max_advance.map(|advance| (f64::from(advance) / metrics.width).floor().max(1.0) as u32);
+        let mut line_start = 0;
+        let mut col = 0;
+        let mut word_start = 0;
+        for (word_end, is_hard_break) in LineBreakIterator::new(text) {
+            let word = &text[word_start..word_end];
+            let word_width = word.trim_end().width() as u32;
+            if max_cols.is_some_and(|max| col > 0 && col + word_width > max) {
+                self.finish_line(line_start, word_start, col);
+                line_start = word_start;
+                col = 0;
+            }
+            for (index, grapheme) in word.grapheme_indices(true) {
+                let start = word_start + index;
+                // Tabs extend to the next tab stop, while line breaks and
+                // other control characters take no room.
+                let width = if grapheme == "\t" {
+                    TAB_WIDTH - col % TAB_WIDTH
+                } else if grapheme.chars().all(char::is_control) {
+                    0
+                } else {
+                    grapheme.width() as u32
+                };
+                let is_whitespace = grapheme.chars().all(char::is_whitespace);
+                // Words wider than a whole line are broken anywhere.
+                if !is_whitespace && max_cols.is_some_and(|max| col > 0 && col + width > max) {
+                    self.finish_line(line_start, start, col);
+                    line_start = start;
+                    col = 0;
+                }
+                self.clusters.push(CellCluster {
+                    text_range: start..start + grapheme.len(),
+                    col,
+                    width,
+                    is_whitespace,
+                });
+                col += width;
+            }
+            word_start = word_end;
+            if is_hard_break && word_end < text.len() {
+                self.finish_line(line_start, word_end, col);
+                line_start = word_end;
+                col = 0;
+            }
+        }
+        // Text ending with a newline has an empty last line.
+        if text.ends_with('\n') {
+            self.finish_line(line_start, text.len(), col);
+            line_start = text.len();
+            col = 0;
+        }
+        self.finish_line(line_start, text.len(), col);
+
+        let container = max_cols.unwrap_or_else(|| {
+            self.lines
+                .iter()
+                .map(|line| line.width)
+                .max()
+                .unwrap_or_default()
+        });
+        for line in &mut self.lines {
+            let space = container.saturating_sub(line.width);
+            line.offset = match alignment {
+                Alignment::Start | Alignment::Justified => 0,
+                Alignment::Middle => space / 2,
+                Alignment::End => space,
+            };
+        }
+    }
+
+    fn finish_line(&mut self, text_start: usize, text_end: usize, full_width: u32) {
+        let first_cluster = self
+            .lines
+            .last()
+            .map(|line| line.clusters.end)
+            .unwrap_or_default();
+        let clusters = first_cluster..self.clusters.len();
+        let width = self.clusters[clusters.clone()]
+            .iter()
+            .rev()
+            .find(|cluster| !cluster.is_whitespace)
+            .map(|cluster| cluster.col + cluster.width)
+            .unwrap_or_default();
+        self.lines.push(CellLine {
+            clusters,
+            text_range: text_start..text_end,
+            offset: 0,
+            width,
+            full_width,
+        });
+    }
+
+    /// The width of the layout, excluding trailing whitespace.
+    pub(crate) fn width(&self) -> f64 {
+        let cols = self.lines.iter().map(|line| line.width).max();
+        f64::from(cols.unwrap_or_default()) * self.metrics.width
+    }
+
+    /// The width of the layout, including trailing whitespace.
+    pub(crate) fn full_width(&self) -> f64 {
+        let cols = self.lines.iter().map(|line| line.full_width).max();
+        f64::from(cols.unwrap_or_default()) * self.metrics.width
+    }
+
+    pub(crate) fn height(&self) -> f64 {
+        self.lines.len() as f64 * self.metrics.height
+    }
+
+    pub(crate) fn first_baseline(&self) -> f64 {
+        self.metrics.height
+    }
+
+    /// The top and bottom of the line at `line_index`.
+    pub(crate) fn line_bounds(&self, line_index: usize) -> (f64, f64) {
+        let top = line_index as f64 * self.metrics.height;
+        (top, top + self.metrics.height)
+    }
+
+    fn line_clusters(&self, line: &CellLine) -> &[CellCluster] {
+        &self.clusters[line.clusters.clone()]
+    }
+
+    /// Returns the cursor for the nearest grapheme boundary to `point`.
+    pub(crate) fn cursor_for_point(&self, point: Point) -> Cursor {
+        let mut cursor = Cursor {
+            is_inside: point.x >= 0. && point.y >= 0.,
+            ..Default::default()
+        };
+        let row = (point.y / self.metrics.height).floor();
+        let line_index = if row >= self.lines.len() as f64 {
+            cursor.is_inside = false;
+            self.lines.len() - 1
+        } else {
+            row.max(0.0) as usize
+        };
+        let line = &self.lines[line_index];
+        let x = point.x / self.metrics.width - f64::from(line.offset);
+
+        cursor.path.line_index = line_index;
+        cursor.baseline = self.line_bounds(line_index).1 as f32;
+        cursor.insert_point = self.line_end(line_index);
+        cursor.offset = self.col_to_x(line, line.full_width);
+        for (cluster_index, cluster) in self.line_clusters(line).iter().enumerate() {
+            let left = f64::from(cluster.col);
+            let right = f64::from(cluster.col + cluster.width);
+            if x >= right || cluster.width == 0 {
+                continue;
+            }
+            cursor.path.cluster_index = cluster_index;
+            cursor.text_start = cluster.text_range.start;
+            cursor.text_end = cluster.text_range.end;
+            cursor.advance = (f64::from(cluster.width) * self.metrics.width) as f32;
+            if x <= (left + right) * 0.5 {
+                cursor.insert_point = cluster.text_range.start;
+                cursor.offset = self.col_to_x(line, cluster.col);
+            } else {
+                cursor.insert_point = cluster.text_range.end;
+                cursor.offset = self.col_to_x(line, cluster.col + cluster.width);
+            }
+            return cursor;
+        }
+        if x > f64::from(line.full_width) {
+            cursor.is_inside = false;
+        }
+        cursor
+    }
+
+    /// Returns the cursor for the grapheme boundary at `text_pos`.
+    pub(crate) fn cursor_for_text_position(&self, text_pos: usize) -> Cursor {
+        let line_index = self
+            .lines
+            .iter()
+            .rposition(|line| line.text_range.start <= text_pos)
+            .unwrap_or_default();
+        let line = &self.lines[line_index];
+        let mut cursor = Cursor {
+            is_inside: true,
+            insert_point: text_pos,
+            baseline: self.line_bounds(line_index).1 as f32,
+            offset: self.col_to_x(line, line.full_width),
+            ..Default::default()
+        };
+        cursor.path.line_index = line_index;
+        for (cluster_index, cluster) in self.line_clusters(line).iter().enumerate() {
+            if cluster.text_range.end <= text_pos {
+                continue;
+            }
+            if cluster.text_range.start > text_pos || cluster.chars_are_control() {
+                break;
+            }
+            cursor.path.cluster_index = cluster_index;
+            cursor.text_start = cluster.text_range.start;
+            cursor.text_end = cluster.text_range.end;
+            cursor.advance = (f64::from(cluster.width) * self.metrics.width) as f32;
+            cursor.offset = self.col_to_x(line, cluster.col);
+            break;
+        }
+        cursor
+    }
+
+    /// The text position of the end of the line at `line_index`, before any line break.
+    fn line_end(&self, line_index: usize) -> usize {
+        let line = &self.lines[line_index];
+        self.line_clusters(line)
+            .iter()
+            .rev()
+            .find(|cluster| !cluster.chars_are_control())
+            .map(|cluster| cluster.text_range.end)
+            .unwrap_or(line.text_range.start)
+    }
+
+    fn col_to_x(&self, line: &CellLine, col: u32) -> f32 {
+        (f64::from(line.offset + col) * self.metrics.width) as f32
+    }
+
+    /// Draws the layout of `text` into `scene`.
+    ///
+    /// `highlight` and `underline` style a range of the text differently from
+    /// the rest.
+    pub(crate) fn draw(
+        &self,
+        scene: &mut Scene,
+        transform: Affine,
+        text: &str,
+        brush: &TextBrush,
+        highlight: Option<&(Range<usize>, TextBrush)>,
+        underline: Option<&Range<usize>>,
+    ) {
+        let style_at = |pos: usize| {
+            let brush = match highlight {
+                Some((range, brush)) if range.contains(&pos) => brush,
+                _ => brush,
+            };
+            let underline = underline.is_some_and(|range| range.contains(&pos));
+            (brush, underline)
+        };
+        for (line_index, line) in self.lines.iter().enumerate() {
+            let (top, bottom) = self.line_bounds(line_index);
+            let mut clusters = self
+                .line_clusters(line)
+                .iter()
+                .filter(|cluster| cluster.width > 0)
+                .peekable();
+            while let Some(first) = clusters.next() {
+                let style = style_at(first.text_range.start);
+                let mut span_text = String::new();
+                let mut last = first;
+                loop {
+                    // Terminals don't draw tabs, so they are drawn as the
+                    // spaces they stand for.
+                    match &text[last.text_range.clone()] {
+                        "\t" => span_text.extend((0..last.width).map(|_| ' ')),
+                        cluster_text => span_text.push_str(cluster_text),
+                    }
+                    let Some(next) = clusters.next_if(|next| {
+                        next.col == last.col + last.width
+                            && style_at(next.text_range.start) == style
+                    }) else {
+                        break;
+                    };
+                    last = next;
+                }
+                let (brush, underline) = style;
+                let rect = Rect::new(
+                    f64::from(self.col_to_x(line, first.col)),
+                    top,
+                    f64::from(self.col_to_x(line, last.col + last.width)),
+                    bottom,
+                );
+                let text_brush = match brush {
+                    TextBrush::Normal(text_brush) => text_brush,
+                    TextBrush::Highlight { text, fill } => {
+                        scene.fill(Fill::NonZero, transform, fill, None, &rect);
+                        text
+                    }
+                };
+                scene.draw_text_span(
+                    transform,
+                    TextSpan {
+                        text: span_text,
+                        rect,
+                        brush: text_brush.clone(),
+                        underline,
+                        strikethrough: false,
+                    },
+                );
+            }
+        }
+    }
+}
+
+impl CellCluster {
+    fn chars_are_control(&self) -> bool {
+        self.width == 0 && self.is_whitespace
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(text: &str, max_cols: Option<u32>, alignment: Alignment) -> CellLayout {
+        let mut layout = CellLayout::default();
+        let max_advance = max_cols.map(|cols| cols as f32 * 8.0);
+        layout.build(text, CellMetrics::TERMINAL, max_advance, alignment);
+        layout
+    }
+
+    fn lines<'a>(layout: &CellLayout, text: &'a str) -> Vec<&'a str> {
+        layout
+            .lines
+            .iter()
+            .map(|line| &text[line.text_range.clone()])
+            .collect()
+    }
+
+    #[test]
+    fn measures_in_cells() {
+        let text = "a語e\u{301} ";
+        let layout = build(text, None, Alignment::Start);
+        assert_eq!(layout.width(), 4.0 * 8.0);
+        assert_eq!(layout.full_width(), 5.0 * 8.0);
+        assert_eq!(layout.height(), 16.0);
+    }
+
+    #[test]
+    fn tabs_extend_to_the_next_tab_stop() {
+        let text = "a\tb\t\tc";
+        let layout = build(text, None, Alignment::Start);
+        assert_eq!(layout.width(), 25.0 * 8.0);
+        let cursor = layout.cursor_for_text_position(2);
+        assert_eq!(cursor.offset, 8.0 * 8.0);
+        let cursor = layout.cursor_for_point(Point::new(12.0 * 8.0, 8.0));
+        assert_eq!(cursor.insert_point, 3);
+
+        let mut scene = Scene::new();
+        layout.draw(
+            &mut scene,
+            Affine::IDENTITY,
+            text,
+            &crate::Color::BLACK.into(),
+            None,
+            None,
+        );
+        let crate::vello::Command::Text(span) = &scene.encoding().commands()[0] else {
+            panic!("expected a text command");
+        };
+        assert_eq!(span.text, format!("a{}b{}c", " ".repeat(7), " ".repeat(15)));
+    }
+
+    #[test]
+    fn wraps_at_line_breaks() {
+        let text = "hello wide world\nabcdefghij";
+        let layout = build(text, Some(8), Alignment::Start);
+        assert_eq!(
+            lines(&layout, text),
+            ["hello ", "wide ", "world\n", "abcdefgh", "ij"]
+        );
+        assert_eq!(layout.width(), 8.0 * 8.0);
+        assert_eq!(layout.height(), 5.0 * 16.0);
+
+        let text = "trailing\n";
+        let layout = build(text, None, Alignment::Start);
+        assert_eq!(lines(&layout, text), ["trailing\n", ""]);
+    }
+
+    #[test]
+    fn aligns_lines() {
+        let text = "ab\nabcd";
+        let layout = build(text, Some(8), Alignment::End);
+        let cursor = layout.cursor_for_text_position(0);
+        assert_eq!(cursor.offset, 6.0 * 8.0);
+        let layout = build(text, None, Alignment::Middle);
+        let cursor = layout.cursor_for_text_position(0);
+        assert_eq!(cursor.offset, 8.0);
+    }
+
+    #[test]
+    fn cursors_round_trip() {
+        let text = "a語b\ncd";
+        let layout = build(text, None, Alignment::Start);
+
+        let cursor = layout.cursor_for_text_position(1);
+        assert_eq!((cursor.offset, cursor.baseline), (8.0, 16.0));
+        let cursor = layout.cursor_for_text_position(4);
+        assert_eq!(cursor.offset, 24.0);
+        // The end of the first line is before the newline.
+        let cursor = layout.cursor_for_text_position(5);
+        assert_eq!((cursor.offset, cursor.path.line_index), (32.0, 0));
+        let cursor = layout.cursor_for_text_position(8);
+        assert_eq!((cursor.offset, cursor.path.line_index), (16.0, 1));
+
+        // Right half of the wide character.
+        let cursor = layout.cursor_for_point(Point::new(20.0, 8.0));
+        assert_eq!(cursor.insert_point, 4);
+        let cursor = layout.cursor_for_point(Point::new(100.0, 8.0));
+        assert_eq!(cursor.insert_point, 5);
+        assert!(!cursor.is_inside);
+        let cursor = layout.cursor_for_point(Point::new(3.0, 20.0));
+        assert_eq!(cursor.insert_point, 6);
+        let cursor = layout.cursor_for_point(Point::new(3.0, 100.0));
+        assert_eq!(cursor.path.line_index, 1);
+    }
+
+    #[test]
+    fn draws_styled_spans() {
+        let text = "one two";
+        let layout = build(text, None, Alignment::Start);
+        let highlight = (
+            4..7,
+            TextBrush::Highlight {
+                text: crate::Color::WHITE.into(),
+                fill: crate::Color::BLUE.into(),
+            },
+        );
+        let mut scene = Scene::new();
+        layout.draw(
+            &mut scene,
+            Affine::IDENTITY,
+            text,
+            &crate::Color::BLACK.into(),
+            Some(&highlight),
+            Some(&(0..3)),
+        );
+
+        let spans: Vec<_> = scene
+            .encoding()
+            .commands()
+            .iter()
+            .filter_map(|command| match command {
+                crate::vello::Command::Text(span) => {
+                    Some((span.text.as_str(), span.rect, span.underline))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                ("one", Rect::new(0.0, 0.0, 24.0, 16.0), true),
+                (" ", Rect::new(24.0, 0.0, 32.0, 16.0), false),
+                ("two", Rect::new(32.0, 0.0, 56.0, 16.0), false),
+            ]
+        );
+    }
+}
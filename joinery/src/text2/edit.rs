@@ -80,16 +80,8 @@ impl<T: EditableText> TextEditor<T> {
         font_ctx: &mut FontContext,
         layout_ctx: &mut LayoutContext<TextBrush>,
     ) {
-        self.inner
-            .rebuild_with_attributes(font_ctx, layout_ctx, |mut builder| {
-                if let Some(range) = self.preedit_range.as_ref() {
-                    builder.push(
-                        &parley::style::StyleProperty::Underline(true),
-                        range.clone(),
-                    );
-                }
-                builder
-            });
+        self.inner.layout.set_underline(self.preedit_range.clone());
+        self.inner.rebuild(font_ctx, layout_ctx);
     }
 
     pub fn draw(&mut self, scene: &mut Scene, point: impl Into<Point>) {
@@ -108,7 +100,7 @@ impl<T: EditableText> TextEditor<T> {
     }
 
     pub fn text_event(&mut self, ctx: &mut EventCtx, event: &TextEvent) -> Handled {
-        let inner_handled = self.inner.text_event(event);
+        let inner_handled = self.inner.text_event(ctx, event);
         if inner_handled.is_handled() {
             return inner_handled;
         }
@@ -255,6 +247,29 @@ impl<T: EditableText> TextEditor<T> {
                                 Handled::No
                             }
                         }
+                        Key::Character(c) if c.eq_ignore_ascii_case("x") => {
+                            let Some(selection) = self.inner.selection else {
+                                return Handled::No;
+                            };
+                            if selection.is_caret() {
+                                return Handled::Yes;
+                            }
+                            if let Some(text) = self.text().slice(selection.range()) {
+                                ctx.clipboard().set_text(text.into_owned());
+                            }
+                            self.text_mut().edit(selection.range(), "");
+                            self.inner.selection =
+                                Some(Selection::caret(selection.min(), Affinity::Downstream));
+                            let contents = self.text().as_str().to_string();
+                            ctx.submit_action(Action::TextChanged(contents));
+                            Handled::Yes
+                        }
+                        Key::Character(c) if c.eq_ignore_ascii_case("v") => {
+                            if let Some(text) = ctx.clipboard().get_text() {
+                                self.insert_pasted_text(ctx, &text);
+                            }
+                            Handled::Yes
+                        }
                         _ => Handled::No,
                     }
                 } else {
@@ -264,8 +279,32 @@ impl<T: EditableText> TextEditor<T> {
             TextEvent::KeyboardKey(_, _) => Handled::No,
             TextEvent::ModifierChange(_) => Handled::No,
             TextEvent::FocusChange(_) => Handled::No,
+            TextEvent::Paste(text) => {
+                self.insert_pasted_text(ctx, text);
+                Handled::Yes
+            }
         }
     }
+
+    /// Replace the selection with pasted `text`.
+    fn insert_pasted_text(&mut self, ctx: &mut EventCtx, text: &str) {
+        let selection = self.inner.selection.unwrap_or(Selection {
+            anchor: 0,
+            active: 0,
+            active_affinity: Affinity::Downstream,
+            h_pos: None,
+        });
+        // Enter submits the text, so the editor only holds a single line:
+        // pasted line breaks become spaces.
+        let text = text.replace("\r\n", " ").replace(['\r', '\n'], " ");
+        self.text_mut().edit(selection.range(), &*text);
+        self.inner.selection = Some(Selection::caret(
+            selection.min() + text.len(),
+            Affinity::Downstream,
+        ));
+        let contents = self.text().as_str().to_string();
+        ctx.submit_action(Action::TextChanged(contents));
+    }
 }
 
 impl<T: EditableText> Deref for TextEditor<T> {
@@ -3,6 +3,7 @@
 
 //! A type for laying out, drawing, and interacting with text.
 
+use std::ops::Range;
 use std::rc::Rc;
 
 use crate::vello::Scene;
@@ -14,7 +15,9 @@ use parley::style::{Brush as BrushTrait, FontFamily, FontStack, GenericFamily, S
 use parley::{FontContext, Layout, LayoutContext};
 use peniko::{self, Color, Gradient};
 
+use super::cell_layout::CellLayout;
 use super::{Link, TextStorage};
+use crate::CellMetrics;
 
 /// A component for displaying text on screen.
 ///
@@ -51,14 +54,33 @@ pub struct TextLayout<T> {
     alignment: Alignment,
     max_advance: Option<f32>,
 
+    highlight: Option<(Range<usize>, TextBrush)>,
+    underline: Option<Range<usize>>,
+
     links: Rc<[(Rect, usize)]>,
 
+    engine: TextEngine,
     needs_layout: bool,
     needs_line_breaks: bool,
     layout: Layout<TextBrush>,
+    cell_layout: CellLayout,
     scratch_scene: Scene,
 }
 
+/// How a [`TextLayout`] measures and positions text.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TextEngine {
+    /// Shape the text with the fonts of the system, through Parley.
+    #[default]
+    Parley,
+    /// Give every grapheme cluster as many cells as its unicode width.
+    ///
+    /// This matches what a terminal displays, and doesn't need any fonts.
+    /// Font properties (family, size, weight and style) are ignored, as are
+    /// the attributes added through Parley builders.
+    Cells(CellMetrics),
+}
+
 /// A custom brush for `Parley`, enabling using Parley to pass-through
 /// which glyphs are selected/highlighted
 #[derive(Clone, Debug, PartialEq)]
@@ -125,11 +147,16 @@ impl<T> TextLayout<T> {
             max_advance: None,
             alignment: Default::default(),
 
+            highlight: None,
+            underline: None,
+
             links: Rc::new([]),
 
+            engine: TextEngine::default(),
             needs_layout: true,
             needs_line_breaks: true,
             layout: Layout::new(),
+            cell_layout: CellLayout::default(),
             scratch_scene: Scene::new(),
         }
     }
@@ -142,6 +169,14 @@ impl<T> TextLayout<T> {
         self.needs_line_breaks = true;
     }
 
+    /// Set the engine used to lay out the text.
+    pub fn set_engine(&mut self, engine: TextEngine) {
+        if engine != self.engine {
+            self.engine = engine;
+            self.invalidate();
+        }
+    }
+
     /// Set the scaling factor
     pub fn set_scale(&mut self, scale: f32) {
         if scale != self.scale {
@@ -163,6 +198,24 @@ impl<T> TextLayout<T> {
         }
     }
 
+    /// Set a range of the text to display with a different brush.
+    ///
+    /// This is typically used to show the selection.
+    pub fn set_highlight(&mut self, highlight: Option<(Range<usize>, TextBrush)>) {
+        if highlight != self.highlight {
+            self.highlight = highlight;
+            self.invalidate();
+        }
+    }
+
+    /// Set a range of the text to underline.
+    pub fn set_underline(&mut self, underline: Option<Range<usize>>) {
+        if underline != self.underline {
+            self.underline = underline;
+            self.invalidate();
+        }
+    }
+
     /// Set the default font stack.
     pub fn set_font(&mut self, font: FontStack<'static>) {
         if font != self.font {
@@ -277,6 +330,8 @@ impl<T: TextStorage> TextLayout<T> {
     }
 
     /// Returns the inner Parley [`Layout`] value.
+    ///
+    /// This is empty unless the engine is [`TextEngine::Parley`].
     pub fn layout(&self) -> &Layout<TextBrush> {
         self.assert_rebuilt("layout");
         &self.layout
@@ -287,7 +342,12 @@ impl<T: TextStorage> TextLayout<T> {
     /// This is not meaningful until [`Self::rebuild`] has been called.
     pub fn size(&self) -> Size {
         self.assert_rebuilt("size");
-        Size::new(self.layout.width().into(), self.layout.height().into())
+        match self.engine {
+            TextEngine::Parley => {
+                Size::new(self.layout.width().into(), self.layout.height().into())
+            }
+            TextEngine::Cells(_) => Size::new(self.cell_layout.width(), self.cell_layout.height()),
+        }
     }
 
     /// The size of the laid-out text, including any trailing whitespace.
@@ -295,7 +355,14 @@ impl<T: TextStorage> TextLayout<T> {
     /// This is not meaningful until [`Self::rebuild`] has been called.
     pub fn full_size(&self) -> Size {
         self.assert_rebuilt("full_size");
-        Size::new(self.layout.full_width().into(), self.layout.height().into())
+        match self.engine {
+            TextEngine::Parley => {
+                Size::new(self.layout.full_width().into(), self.layout.height().into())
+            }
+            TextEngine::Cells(_) => {
+                Size::new(self.cell_layout.full_width(), self.cell_layout.height())
+            }
+        }
     }
 
     /// Return the text's [`LayoutMetrics`].
@@ -304,6 +371,13 @@ impl<T: TextStorage> TextLayout<T> {
     pub fn layout_metrics(&self) -> LayoutMetrics {
         self.assert_rebuilt("layout_metrics");
 
+        if let TextEngine::Cells(_) = self.engine {
+            return LayoutMetrics {
+                size: self.size(),
+                first_baseline: self.cell_layout.first_baseline() as f32,
+                trailing_whitespace_width: self.cell_layout.full_width() as f32,
+            };
+        }
         let first_baseline = self.layout.get(0).unwrap().metrics().baseline;
         let size = Size::new(self.layout.width().into(), self.layout.height().into());
         LayoutMetrics {
@@ -323,7 +397,10 @@ impl<T: TextStorage> TextLayout<T> {
         // TODO: This is a mostly good first pass, but doesn't handle cursor positions in
         // grapheme clusters within a parley cluster.
         // We can also try
-        Cursor::from_point(&self.layout, point.x as f32, point.y as f32)
+        match self.engine {
+            TextEngine::Parley => Cursor::from_point(&self.layout, point.x as f32, point.y as f32),
+            TextEngine::Cells(_) => self.cell_layout.cursor_for_point(point),
+        }
     }
 
     /// Given the utf-8 position of a character boundary in the underlying text,
@@ -344,7 +421,10 @@ impl<T: TextStorage> TextLayout<T> {
         // https://raphlinus.github.io/text/2020/10/26/text-layout.html#shaping-cluster
         // But we're choosing to defer this work
         // This also needs to handle affinity.
-        Cursor::from_position(&self.layout, text_pos, true)
+        match self.engine {
+            TextEngine::Parley => Cursor::from_position(&self.layout, text_pos, true),
+            TextEngine::Cells(_) => self.cell_layout.cursor_for_text_position(text_pos),
+        }
     }
 
     /// Given the utf-8 position of a character boundary in the underlying text,
@@ -384,6 +464,12 @@ impl<T: TextStorage> TextLayout<T> {
     pub fn cursor_line_for_text_position(&self, text_pos: usize) -> Line {
         let from_position = self.cursor_for_text_position(text_pos);
 
+        if let TextEngine::Cells(_) = self.engine {
+            let (top, bottom) = self.cell_layout.line_bounds(from_position.path.line_index);
+            let x = from_position.offset as f64;
+            return Line::new((x, bottom), (x, top));
+        }
+
         let line = from_position.path.line(&self.layout).unwrap();
         let line_metrics = line.metrics();
 
@@ -421,6 +507,8 @@ impl<T: TextStorage> TextLayout<T> {
     /// This method should be called whenever any of these things may have changed.
     /// A simple way to ensure this is correct is to always call this method
     /// as part of your widget's [`layout`][crate::Widget::layout] method.
+    ///
+    /// The contexts are unused with [`TextEngine::Cells`].
     pub fn rebuild(
         &mut self,
         font_ctx: &mut FontContext,
@@ -440,6 +528,19 @@ impl<T: TextStorage> TextLayout<T> {
             RangedBuilder<'b, TextBrush, &'b str>,
         ) -> RangedBuilder<'b, TextBrush, &'b str>,
     ) {
+        if let TextEngine::Cells(metrics) = self.engine {
+            if self.needs_rebuild() {
+                self.needs_layout = false;
+                self.needs_line_breaks = false;
+                self.cell_layout.build(
+                    self.text.as_str(),
+                    metrics,
+                    self.max_advance,
+                    self.alignment,
+                );
+            }
+            return;
+        }
         if self.needs_layout {
             self.needs_layout = false;
 
@@ -451,7 +552,13 @@ impl<T: TextStorage> TextLayout<T> {
             builder.push_default(&StyleProperty::FontStyle(self.style));
             // For more advanced features (e.g. variable font axes), these can be set in add_attributes
 
-            let builder = self.text.add_attributes(builder);
+            let mut builder = self.text.add_attributes(builder);
+            if let Some((range, brush)) = &self.highlight {
+                builder.push(&StyleProperty::Brush(brush.clone()), range.clone());
+            }
+            if let Some(range) = &self.underline {
+                builder.push(&StyleProperty::Underline(true), range.clone());
+            }
             let mut builder = attributes(builder);
             builder.build_into(&mut self.layout);
 
@@ -479,11 +586,23 @@ impl<T: TextStorage> TextLayout<T> {
         self.assert_rebuilt("draw");
         // TODO: This translation doesn't seem great
         let p: Point = point.into();
+        if let TextEngine::Cells(_) = self.engine {
+            self.cell_layout.draw(
+                scene,
+                Affine::translate((p.x, p.y)),
+                self.text.as_str(),
+                &self.brush,
+                self.highlight.as_ref(),
+                self.underline.as_ref(),
+            );
+            return;
+        }
         crate::text_helpers::render_text(
             scene,
             &mut self.scratch_scene,
             Affine::translate((p.x, p.y)),
             &self.layout,
+            self.text.as_str(),
         );
     }
 }
@@ -499,6 +618,7 @@ impl<T: TextStorage> std::fmt::Debug for TextLayout<T> {
             .field("weight", &self.weight)
             .field("style", &self.style)
             .field("alignment", &self.alignment)
+            .field("engine", &self.engine)
             .field("wrap_width", &self.max_advance)
             .field("outdated?", &self.needs_rebuild())
             .field("width", &self.layout.width())
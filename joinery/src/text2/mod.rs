@@ -14,7 +14,9 @@ mod store;
 pub use store::{Link, TextStorage};
 
 mod layout;
-pub use layout::{LayoutMetrics, TextBrush, TextLayout};
+pub use layout::{LayoutMetrics, TextBrush, TextEngine, TextLayout};
+
+mod cell_layout;
 
 mod selection;
 pub use selection::{
@@ -16,7 +16,7 @@ use crate::terminal::event::KeyEvent;
 use crate::terminal::keyboard;
 use crate::vello::peniko::{Brush, Color};
 use crate::vello::Scene;
-use crate::{Handled, TextEvent};
+use crate::{EventCtx, Handled, TextEvent};
 
 use super::{TextBrush, TextLayout, TextStorage};
 
@@ -116,7 +116,7 @@ impl<T: Selectable> TextWithSelection<T> {
         }
     }
 
-    pub fn text_event(&mut self, event: &TextEvent) -> Handled {
+    pub fn text_event(&mut self, ctx: &mut EventCtx, event: &TextEvent) -> Handled {
         use keyboard::{Key, NamedKey};
         match event {
             TextEvent::KeyboardKey(key, mods) if key.state.is_pressed() => {
@@ -178,7 +178,7 @@ impl<T: Selectable> TextWithSelection<T> {
                             // e.g. to put HTML code if supported by the rich text kind
                             if let Some(text) = self.text().slice(selection.min()..selection.max())
                             {
-                                println!(r#"Copying "{text}""#);
+                                ctx.clipboard().set_text(text.into_owned());
                             } else {
                                 debug_panic!("Had invalid selection");
                             }
@@ -200,6 +200,7 @@ impl<T: Selectable> TextWithSelection<T> {
                 // TODO: Set our highlighting colour to a lighter blue if window unfocused
                 Handled::No
             }
+            TextEvent::Paste(_) => Handled::No,
         }
     }
 
@@ -236,24 +237,25 @@ impl<T: Selectable> TextWithSelection<T> {
         // In theory, we could be clever here and only rebuild the layout if the
         // selected range was previously or currently non-zero size (i.e. there is a selected range)
         if self.needs_selection_update || self.layout.needs_rebuild() {
+            let highlight = self
+                .selection
+                .map(|selection| selection.range())
+                .filter(|range| !range.is_empty())
+                .map(|range| (range, self.highlight_brush.clone()));
+            self.layout.set_highlight(highlight);
             self.layout.invalidate();
             self.layout
-                .rebuild_with_attributes(font_ctx, layout_ctx, |mut builder| {
-                    if let Some(selection) = self.selection {
-                        let range = selection.range();
-                        if !range.is_empty() {
-                            builder.push(
-                                &parley::style::StyleProperty::Brush(self.highlight_brush.clone()),
-                                range,
-                            );
-                        }
-                    }
-                    attributes(builder)
-                });
+                .rebuild_with_attributes(font_ctx, layout_ctx, attributes);
             self.needs_selection_update = false;
         }
     }
 
+    /// The line drawn for the cursor by the last call to [`draw`](Self::draw),
+    /// relative to the point the text was drawn at.
+    pub fn cursor_line(&self) -> Option<Line> {
+        self.cursor_line
+    }
+
     pub fn draw(&mut self, scene: &mut Scene, point: impl Into<Point>) {
         // TODO: Calculate the location for this in layout lazily?
         if let Some(selection) = self.selection {
@@ -275,20 +277,13 @@ impl<T: Selectable> TextWithSelection<T> {
     }
 }
 
-/// Get the key which should be used for shortcuts from the underlying event
-///
-/// `key_without_modifiers` is only available on some platforms
-fn shortcut_key(_key: &KeyEvent) -> keyboard::Key {
-    unimplemented!("deal with this")
-    // #[cfg(not(any(target_os = "android", target_os = "ios")))]
-    // {
-    //     use winit::platform::modifier_supplement::KeyEventExtModifierSupplement;
-    //     key.key_without_modifiers()
-    // }
-    // #[cfg(any(target_os = "android", target_os = "ios"))]
-    // // We think it will be rare that users are using a physical keyboard with Android,
-    // // and so we don't really need to worry *too much* about the text selection shortcuts
-    // key.logical_key.clone()
+/// The key pressed, ignoring shift, so that shortcuts work the same with
+/// caps lock on.
+pub(crate) fn shortcut_key(key: &KeyEvent) -> keyboard::Key {
+    match &key.logical_key {
+        keyboard::Key::Character(chr) => keyboard::Key::Character(chr.to_lowercase().into()),
+        key => key.clone(),
+    }
 }
 
 impl<T: Selectable> Deref for TextWithSelection<T> {
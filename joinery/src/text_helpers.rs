@@ -3,8 +3,8 @@
 
 //! Helper functions for working with text in Masonry.
 
-use crate::vello::{kurbo::Affine, peniko::Fill, Scene};
-use kurbo::{Line, Rect, Stroke};
+use crate::vello::{kurbo::Affine, peniko::Fill, Scene, TextSpan};
+use kurbo::Rect;
 use parley::Layout;
 
 use crate::{text2::TextBrush, WidgetId};
@@ -47,135 +47,152 @@ pub enum ImeChangeSignal {
     Reset,
 }
 
-/// A function that renders laid out glyphs to a [Scene].
+/// A function that renders laid out text to a [Scene].
+///
+/// Terminals can't draw glyph outlines, so rather than emitting glyph runs this
+/// walks the clusters of each line and draws the slice of `text` they come
+/// from, split into spans of the same style. `text` must be the text `layout`
+/// was built from.
 pub fn render_text(
     scene: &mut Scene,
     scratch_scene: &mut Scene,
     transform: Affine,
     layout: &Layout<TextBrush>,
+    text: &str,
 ) {
     scratch_scene.reset();
     for line in layout.lines() {
-        let metrics = &line.metrics();
-        for glyph_run in line.glyph_runs() {
-            let mut x = glyph_run.offset();
-            let y = glyph_run.baseline();
-            let run = glyph_run.run();
-            let font = run.font();
-            let font_size = run.font_size();
-            let synthesis = run.synthesis();
-            let glyph_xform = synthesis
-                .skew()
-                .map(|angle| Affine::skew(angle.to_radians().tan() as f64, 0.0));
-            let style = glyph_run.style();
-            let text_brush = match &style.brush {
-                TextBrush::Normal(text_brush) => text_brush,
-                TextBrush::Highlight { text, fill } => {
-                    scene.fill(
-                        Fill::EvenOdd,
-                        transform,
-                        fill,
-                        None,
-                        &Rect::from_origin_size(
-                            (
-                                glyph_run.offset() as f64,
-                                // The y coordinate is on the baseline. We want to draw from the top of the line
-                                // (Note that we are in a y-down coordinate system)
-                                (y - metrics.ascent - metrics.leading) as f64,
-                            ),
-                            (glyph_run.advance() as f64, metrics.size() as f64),
-                        ),
-                    );
-
-                    text
+        let metrics = line.metrics();
+        // The baseline is in a y-down coordinate system. We want the top of the line.
+        let top = (metrics.baseline - metrics.ascent - metrics.leading) as f64;
+        let height = metrics.size() as f64;
+        let mut x = metrics.offset as f64;
+        for run in line.runs() {
+            let mut span: Option<StyleSpan> = None;
+            for cluster in run.visual_clusters() {
+                let style_index = cluster
+                    .glyphs()
+                    .next()
+                    .map(|glyph| glyph.style_index())
+                    .or(span.as_ref().map(|span| span.style_index))
+                    .unwrap_or_default();
+                if span.as_ref().map(|span| span.style_index) != Some(style_index) {
+                    if let Some(span) = span.take() {
+                        span.draw(scene, scratch_scene, transform, layout, top, height);
+                    }
+                    span = Some(StyleSpan {
+                        style_index,
+                        text: String::new(),
+                        x0: x,
+                        x1: x,
+                    });
+                }
+                let span = span.as_mut().unwrap();
+                // Line breaks are part of the last cluster of their line.
+                let cluster_text = &text[cluster.text_range()];
+                if !cluster_text.chars().all(char::is_control) {
+                    span.text.push_str(cluster_text);
                 }
-            };
-            scratch_scene
-                .draw_glyphs(font)
-                .brush(text_brush)
-                .transform(transform)
-                .glyph_transform(glyph_xform)
-                .font_size(font_size)
-                .draw(
-                    Fill::NonZero,
-                    glyph_run.glyphs().map(|glyph| {
-                        let gx = x + glyph.x;
-                        let gy = y - glyph.y;
-                        x += glyph.advance;
-                        crate::vello::glyph::Glyph {
-                            id: glyph.id as _,
-                            x: gx,
-                            y: gy,
-                        }
-                    }),
-                );
-            if let Some(underline) = &style.underline {
-                let underline_brush = match &underline.brush {
-                    TextBrush::Normal(text) => text,
-                    // It doesn't make sense for an underline to have a highlight colour, so we
-                    // just use the text colour for the colour
-                    TextBrush::Highlight { text, .. } => text,
-                };
-                let run_metrics = glyph_run.run().metrics();
-                let offset = match underline.offset {
-                    Some(offset) => offset,
-                    None => run_metrics.underline_offset,
-                };
-                let width = match underline.size {
-                    Some(size) => size,
-                    None => run_metrics.underline_size,
-                };
-                // The `offset` is the distance from the baseline to the *top* of the underline
-                // so we move the line down by half the width
-                // Remember that we are using a y-down coordinate system
-                let y = glyph_run.baseline() - offset + width / 2.;
-
-                let line = Line::new(
-                    (glyph_run.offset() as f64, y as f64),
-                    ((glyph_run.offset() + glyph_run.advance()) as f64, y as f64),
-                );
-                scratch_scene.stroke(
-                    &Stroke::new(width.into()),
-                    transform,
-                    underline_brush,
-                    None,
-                    &line,
-                );
+                x += cluster.advance() as f64;
+                span.x1 = x;
             }
-            if let Some(strikethrough) = &style.strikethrough {
-                let strikethrough_brush = match &strikethrough.brush {
-                    TextBrush::Normal(text) => text,
-                    // It doesn't make sense for an underline to have a highlight colour, so we
-                    // just use the text colour for the colour
-                    TextBrush::Highlight { text, .. } => text,
-                };
-                let run_metrics = glyph_run.run().metrics();
-                let offset = match strikethrough.offset {
-                    Some(offset) => offset,
-                    None => run_metrics.strikethrough_offset,
-                };
-                let width = match strikethrough.size {
-                    Some(size) => size,
-                    None => run_metrics.strikethrough_size,
-                };
-                // The `offset` is the distance from the baseline to the *top* of the strikethrough
-                // so we move the line down by half the width
-                // Remember that we are using a y-down coordinate system
-                let y = glyph_run.baseline() - offset + width / 2.;
-
-                let line = Line::new(
-                    (glyph_run.offset() as f64, y as f64),
-                    ((glyph_run.offset() + glyph_run.advance()) as f64, y as f64),
-                );
-                scratch_scene.stroke(
-                    &Stroke::new(width.into()),
-                    transform,
-                    strikethrough_brush,
-                    None,
-                    &line,
-                );
+            if let Some(span) = span {
+                span.draw(scene, scratch_scene, transform, layout, top, height);
             }
         }
     }
     scene.append(scratch_scene, None);
 }
+
+/// Consecutive clusters of a line sharing the same style.
+struct StyleSpan {
+    style_index: usize,
+    /// The text of the clusters, in visual order.
+    text: String,
+    x0: f64,
+    x1: f64,
+}
+
+impl StyleSpan {
+    fn draw(
+        self,
+        scene: &mut Scene,
+        scratch_scene: &mut Scene,
+        transform: Affine,
+        layout: &Layout<TextBrush>,
+        top: f64,
+        height: f64,
+    ) {
+        let Some(style) = layout.styles().get(self.style_index) else {
+            return;
+        };
+        let rect = Rect::new(self.x0, top, self.x1, top + height);
+        let text_brush = match &style.brush {
+            TextBrush::Normal(text_brush) => text_brush,
+            TextBrush::Highlight { text, fill } => {
+                scene.fill(Fill::EvenOdd, transform, fill, None, &rect);
+                text
+            }
+        };
+        scratch_scene.draw_text_span(
+            transform,
+            TextSpan {
+                text: self.text,
+                rect,
+                brush: text_brush.clone(),
+                underline: style.underline.is_some(),
+                strikethrough: style.strikethrough.is_some(),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parley::{FontContext, LayoutContext};
+
+    use crate::text2::{TextEngine, TextLayout};
+    use crate::vello::Command;
+    use crate::{CellMetrics, Color};
+
+    use super::*;
+
+    fn spans(scene: &Scene) -> Vec<(String, Rect)> {
+        scene
+            .encoding()
+            .commands()
+            .iter()
+            .filter_map(|command| match command {
+                Command::Text(span) => Some((span.text.clone(), span.rect)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn text_is_drawn_from_clusters() {
+        let mut font_ctx = FontContext::default();
+        let mut layout_ctx = LayoutContext::new();
+        let mut layout = TextLayout::new("Hello 世界\nagain", 16.0);
+        // Laid out in cells, so that the result doesn't depend on the installed fonts.
+        layout.set_engine(TextEngine::Cells(CellMetrics::UNIT));
+        layout.set_brush(Color::RED);
+        layout.rebuild(&mut font_ctx, &mut layout_ctx);
+
+        let mut scene = Scene::new();
+        layout.draw(&mut scene, (10.0, 20.0));
+
+        assert_eq!(
+            spans(&scene),
+            [
+                ("Hello 世界".into(), Rect::new(10.0, 20.0, 20.0, 21.0)),
+                ("again".into(), Rect::new(10.0, 21.0, 15.0, 22.0)),
+            ]
+        );
+
+        let Command::Text(span) = &scene.encoding().commands()[0] else {
+            panic!("expected a text command");
+        };
+        assert_eq!(span.brush, Color::RED.into());
+    }
+}
@@ -0,0 +1,132 @@
+//! Timers requested by widgets.
+
+use std::collections::BTreeMap;
+use std::num::NonZeroU64;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::WidgetId;
+
+/// Identifies a timer requested with
+/// [`request_timer`](crate::EventCtx::request_timer).
+///
+/// When the timer expires, the widget that requested it receives a
+/// [`LifeCycle::Timer`](crate::LifeCycle::Timer) with this token.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TimerToken(NonZeroU64);
+
+impl TimerToken {
+    fn next() -> TimerToken {
+        static TIMER_TOKEN_COUNTER: AtomicU64 = AtomicU64::new(1);
+        let id = TIMER_TOKEN_COUNTER.fetch_add(1, Ordering::Relaxed);
+        TimerToken(id.try_into().unwrap())
+    }
+
+    pub fn to_raw(self) -> u64 {
+        self.0.into()
+    }
+}
+
+/// The pending timers of a window, ordered by deadline.
+pub(crate) struct TimerQueue {
+    /// The time of the last call to [`advance`](Self::advance), if time only
+    /// moves when the queue is advanced, as in tests. Otherwise deadlines are
+    /// computed from the system clock.
+    virtual_now: Option<Instant>,
+    timers: BTreeMap<(Instant, TimerToken), WidgetId>,
+}
+
+impl TimerQueue {
+    /// A queue following the system clock.
+    pub(crate) fn new() -> Self {
+        TimerQueue {
+            virtual_now: None,
+            timers: BTreeMap::new(),
+        }
+    }
+
+    /// Stop following the system clock: time starts at `now`, and then only
+    /// moves when the queue is advanced.
+    pub(crate) fn use_virtual_time(&mut self, now: Instant) {
+        self.virtual_now = Some(now);
+    }
+
+    /// Adds a timer for `widget` expiring `delay` after now.
+    pub(crate) fn request(&mut self, widget: WidgetId, delay: Duration) -> TimerToken {
+        let token = TimerToken::next();
+        let now = self.virtual_now.unwrap_or_else(Instant::now);
+        self.timers.insert((now + delay, token), widget);
+        token
+    }
+
+    /// The deadline of the next timer to expire.
+    pub(crate) fn next_deadline(&self) -> Option<Instant> {
+        self.timers.keys().next().map(|(deadline, _)| *deadline)
+    }
+
+    /// Removes the timers expired by `now`, in the order they expired.
+    ///
+    /// With virtual time, this also moves the time of the queue to `now`.
+    pub(crate) fn advance(&mut self, now: Instant) -> Vec<(TimerToken, WidgetId)> {
+        let now = match &mut self.virtual_now {
+            Some(virtual_now) => {
+                *virtual_now = (*virtual_now).max(now);
+                *virtual_now
+            }
+            None => now,
+        };
+        let mut expired = Vec::new();
+        while let Some(entry) = self.timers.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+            let ((_, token), widget) = entry.remove_entry();
+            expired.push((token, widget));
+        }
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timers_expire_in_deadline_order() {
+        let start = Instant::now();
+        let [a, b] = [WidgetId::next(), WidgetId::next()];
+        let mut queue = TimerQueue::new();
+        queue.use_virtual_time(start);
+        let late = queue.request(a, Duration::from_millis(300));
+        let early = queue.request(b, Duration::from_millis(100));
+        assert_eq!(
+            queue.next_deadline(),
+            Some(start + Duration::from_millis(100))
+        );
+
+        assert_eq!(queue.advance(start + Duration::from_millis(50)), []);
+        assert_eq!(
+            queue.advance(start + Duration::from_millis(100)),
+            [(early, b)]
+        );
+        assert_eq!(
+            queue.advance(start + Duration::from_millis(400)),
+            [(late, a)]
+        );
+        assert_eq!(queue.next_deadline(), None);
+    }
+
+    #[test]
+    fn deadlines_follow_the_system_clock() {
+        let mut queue = TimerQueue::new();
+        // Advancing the queue doesn't change when later timers expire.
+        queue.advance(Instant::now() - Duration::from_secs(60));
+        let before = Instant::now();
+        queue.request(WidgetId::next(), Duration::from_millis(100));
+        let after = Instant::now();
+
+        let deadline = queue.next_deadline().unwrap();
+        assert!(deadline >= before + Duration::from_millis(100));
+        assert!(deadline <= after + Duration::from_millis(100));
+    }
+}
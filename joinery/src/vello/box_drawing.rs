@@ -0,0 +1,236 @@
+//! Unicode box-drawing characters, and how they merge where lines meet.
+
+/// The weight of a box-drawing line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum LineWeight {
+    #[default]
+    None,
+    Light,
+    Heavy,
+    Double,
+}
+
+impl LineWeight {
+    /// The weight used to draw a stroke `width` logical pixels wide.
+    ///
+    /// Strokes up to 2 pixels wide (such as button and textbox borders) are
+    /// light, strokes up to 4 pixels are heavy and wider ones are double.
+    pub(crate) fn for_stroke_width(width: f64) -> LineWeight {
+        if width <= 2.0 {
+            LineWeight::Light
+        } else if width <= 4.0 {
+            LineWeight::Heavy
+        } else {
+            LineWeight::Double
+        }
+    }
+
+    fn from_index(index: u8) -> LineWeight {
+        match index {
+            0 => LineWeight::None,
+            1 => LineWeight::Light,
+            2 => LineWeight::Heavy,
+            _ => LineWeight::Double,
+        }
+    }
+}
+
+/// The lines going from the center of a cell to each of its edges.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Arms {
+    pub up: LineWeight,
+    pub right: LineWeight,
+    pub down: LineWeight,
+    pub left: LineWeight,
+    /// Whether a corner should be drawn with an arc.
+    pub rounded: bool,
+}
+
+impl Arms {
+    pub(crate) fn horizontal(weight: LineWeight) -> Arms {
+        Arms {
+            right: weight,
+            left: weight,
+            ..Arms::default()
+        }
+    }
+
+    pub(crate) fn vertical(weight: LineWeight) -> Arms {
+        Arms {
+            up: weight,
+            down: weight,
+            ..Arms::default()
+        }
+    }
+
+    pub(crate) fn is_empty(self) -> bool {
+        self.as_array() == [LineWeight::None; 4]
+    }
+
+    /// Combines the lines of two boxes meeting in the same cell, keeping the
+    /// heaviest line of each arm.
+    ///
+    /// The result is only rounded if both are, so that a rounded corner over a
+    /// square one stays square.
+    pub(crate) fn merge(self, other: Arms) -> Arms {
+        if self.is_empty() {
+            return other;
+        }
+        Arms {
+            up: self.up.max(other.up),
+            right: self.right.max(other.right),
+            down: self.down.max(other.down),
+            left: self.left.max(other.left),
+            rounded: self.rounded && other.rounded,
+        }
+    }
+
+    /// The box-drawing character for these arms.
+    ///
+    /// Unicode has no characters mixing heavy and double lines, nor for some
+    /// junctions of double and light lines: those are drawn with lighter lines.
+    pub(crate) fn symbol(self) -> Option<char> {
+        if self.rounded {
+            use LineWeight::{Light, None};
+            let arc = match self.as_array() {
+                [None, Light, Light, None] => Some('╭'),
+                [None, None, Light, Light] => Some('╮'),
+                [Light, None, None, Light] => Some('╯'),
+                [Light, Light, None, None] => Some('╰'),
+                _ => Option::None,
+            };
+            if arc.is_some() {
+                return arc;
+            }
+        }
+        let arms = self.as_array();
+        let lighten = |from: LineWeight| {
+            arms.map(|weight| {
+                if weight == from {
+                    LineWeight::Light
+                } else {
+                    weight
+                }
+            })
+        };
+        let all_light = arms.map(|weight| weight.min(LineWeight::Light));
+        [
+            arms,
+            lighten(LineWeight::Heavy),
+            lighten(LineWeight::Double),
+            all_light,
+        ]
+        .into_iter()
+        .find_map(find_symbol)
+    }
+
+    /// The arms in `[up, right, down, left]` order.
+    fn as_array(self) -> [LineWeight; 4] {
+        [self.up, self.right, self.down, self.left]
+    }
+}
+
+fn find_symbol(arms: [LineWeight; 4]) -> Option<char> {
+    SYMBOLS
+        .iter()
+        .find(|(_, weights)| weights.map(LineWeight::from_index) == arms)
+        .map(|(symbol, _)| *symbol)
+}
+
+/// The box-drawing characters with the weights of their arms, in
+/// `[up, right, down, left]` order: 0 for none, 1 for light, 2 for heavy and
+/// 3 for double.
+#[rustfmt::skip]
+const SYMBOLS: &[(char, [u8; 4])] = &[
+    ('╵', [1, 0, 0, 0]), ('╶', [0, 1, 0, 0]), ('╷', [0, 0, 1, 0]), ('╴', [0, 0, 0, 1]),
+    ('╹', [2, 0, 0, 0]), ('╺', [0, 2, 0, 0]), ('╻', [0, 0, 2, 0]), ('╸', [0, 0, 0, 2]),
+    ('─', [0, 1, 0, 1]), ('━', [0, 2, 0, 2]), ('═', [0, 3, 0, 3]),
+    ('│', [1, 0, 1, 0]), ('┃', [2, 0, 2, 0]), ('║', [3, 0, 3, 0]),
+    ('╼', [0, 2, 0, 1]), ('╾', [0, 1, 0, 2]), ('╽', [1, 0, 2, 0]), ('╿', [2, 0, 1, 0]),
+    ('┌', [0, 1, 1, 0]), ('┍', [0, 2, 1, 0]), ('┎', [0, 1, 2, 0]), ('┏', [0, 2, 2, 0]),
+    ('┐', [0, 0, 1, 1]), ('┑', [0, 0, 1, 2]), ('┒', [0, 0, 2, 1]), ('┓', [0, 0, 2, 2]),
+    ('└', [1, 1, 0, 0]), ('┕', [1, 2, 0, 0]), ('┖', [2, 1, 0, 0]), ('┗', [2, 2, 0, 0]),
+    ('┘', [1, 0, 0, 1]), ('┙', [1, 0, 0, 2]), ('┚', [2, 0, 0, 1]), ('┛', [2, 0, 0, 2]),
+    ('├', [1, 1, 1, 0]), ('┝', [1, 2, 1, 0]), ('┞', [2, 1, 1, 0]), ('┟', [1, 1, 2, 0]),
+    ('┠', [2, 1, 2, 0]), ('┡', [2, 2, 1, 0]), ('┢', [1, 2, 2, 0]), ('┣', [2, 2, 2, 0]),
+    ('┤', [1, 0, 1, 1]), ('┥', [1, 0, 1, 2]), ('┦', [2, 0, 1, 1]), ('┧', [1, 0, 2, 1]),
+    ('┨', [2, 0, 2, 1]), ('┩', [2, 0, 1, 2]), ('┪', [1, 0, 2, 2]), ('┫', [2, 0, 2, 2]),
+    ('┬', [0, 1, 1, 1]), ('┭', [0, 1, 1, 2]), ('┮', [0, 2, 1, 1]), ('┯', [0, 2, 1, 2]),
+    ('┰', [0, 1, 2, 1]), ('┱', [0, 1, 2, 2]), ('┲', [0, 2, 2, 1]), ('┳', [0, 2, 2, 2]),
+    ('┴', [1, 1, 0, 1]), ('┵', [1, 1, 0, 2]), ('┶', [1, 2, 0, 1]), ('┷', [1, 2, 0, 2]),
+    ('┸', [2, 1, 0, 1]), ('┹', [2, 1, 0, 2]), ('┺', [2, 2, 0, 1]), ('┻', [2, 2, 0, 2]),
+    ('┼', [1, 1, 1, 1]), ('┽', [1, 1, 1, 2]), ('┾', [1, 2, 1, 1]), ('┿', [1, 2, 1, 2]),
+    ('╀', [2, 1, 1, 1]), ('╁', [1, 1, 2, 1]), ('╂', [2, 1, 2, 1]), ('╃', [2, 1, 1, 2]),
+    ('╄', [2, 2, 1, 1]), ('╅', [1, 1, 2, 2]), ('╆', [1, 2, 2, 1]), ('╇', [2, 2, 1, 2]),
+    ('╈', [1, 2, 2, 2]), ('╉', [2, 1, 2, 2]), ('╊', [2, 2, 2, 1]), ('╋', [2, 2, 2, 2]),
+    ('╒', [0, 3, 1, 0]), ('╓', [0, 1, 3, 0]), ('╔', [0, 3, 3, 0]),
+    ('╕', [0, 0, 1, 3]), ('╖', [0, 0, 3, 1]), ('╗', [0, 0, 3, 3]),
+    ('╘', [1, 3, 0, 0]), ('╙', [3, 1, 0, 0]), ('╚', [3, 3, 0, 0]),
+    ('╛', [1, 0, 0, 3]), ('╜', [3, 0, 0, 1]), ('╝', [3, 0, 0, 3]),
+    ('╞', [1, 3, 1, 0]), ('╟', [3, 1, 3, 0]), ('╠', [3, 3, 3, 0]),
+    ('╡', [1, 0, 1, 3]), ('╢', [3, 0, 3, 1]), ('╣', [3, 0, 3, 3]),
+    ('╤', [0, 3, 1, 3]), ('╥', [0, 1, 3, 1]), ('╦', [0, 3, 3, 3]),
+    ('╧', [1, 3, 0, 3]), ('╨', [3, 1, 0, 1]), ('╩', [3, 3, 0, 3]),
+    ('╪', [1, 3, 1, 3]), ('╫', [3, 1, 3, 1]), ('╬', [3, 3, 3, 3]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIGHT: LineWeight = LineWeight::Light;
+    const HEAVY: LineWeight = LineWeight::Heavy;
+    const DOUBLE: LineWeight = LineWeight::Double;
+
+    fn corner(down_right: LineWeight, rounded: bool) -> Arms {
+        Arms {
+            right: down_right,
+            down: down_right,
+            rounded,
+            ..Arms::default()
+        }
+    }
+
+    #[test]
+    fn junctions_merge_lines() {
+        let tee = corner(LIGHT, false).merge(Arms::horizontal(LIGHT));
+        assert_eq!(tee.symbol(), Some('┬'));
+        let cross = tee.merge(Arms::vertical(HEAVY));
+        assert_eq!(cross.symbol(), Some('╂'));
+        assert_eq!(
+            Arms::horizontal(DOUBLE)
+                .merge(Arms::vertical(LIGHT))
+                .symbol(),
+            Some('╪')
+        );
+    }
+
+    #[test]
+    fn rounded_corners_only_stay_alone() {
+        assert_eq!(corner(LIGHT, true).symbol(), Some('╭'));
+        assert_eq!(corner(HEAVY, true).symbol(), Some('┏'));
+        assert_eq!(
+            corner(LIGHT, true).merge(corner(LIGHT, false)).symbol(),
+            Some('┌')
+        );
+        assert_eq!(
+            corner(LIGHT, true).merge(Arms::vertical(LIGHT)).symbol(),
+            Some('├')
+        );
+    }
+
+    #[test]
+    fn unsupported_mixes_fall_back_to_lighter_lines() {
+        // There is no character mixing heavy and double lines.
+        let arms = Arms::horizontal(DOUBLE).merge(Arms::vertical(HEAVY));
+        assert_eq!(arms.symbol(), Some('╪'));
+        // Nor one with a double line ending on a light one.
+        let arms = Arms {
+            up: DOUBLE,
+            right: LIGHT,
+            down: LIGHT,
+            ..Arms::default()
+        };
+        assert_eq!(arms.symbol(), Some('├'));
+    }
+}
@@ -0,0 +1,257 @@
+//! Reduction of peniko colors to what a terminal can display.
+
+use peniko::Color;
+use ratatui::style::Color as CellColor;
+
+/// The set of colors a terminal can display.
+///
+/// Scenes are painted with arbitrary RGBA colors, which the renderer maps to
+/// the nearest color available at this depth.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ColorDepth {
+    /// 24-bit RGB colors.
+    #[default]
+    TrueColor,
+    /// The 256 colors of the xterm palette.
+    ///
+    /// Only the 6×6×6 color cube and the grayscale ramp are used, since the
+    /// first 16 colors are usually themed by the user.
+    Ansi256,
+    /// The 16 standard ANSI colors, as supported by the Linux console.
+    Ansi16,
+    /// No colors at all: only the terminal's default foreground and
+    /// background, and reverse video for backgrounds that stand out.
+    Monochrome,
+}
+
+/// The xterm defaults for the 16 ANSI colors, in palette order.
+const ANSI_16: [(CellColor, [u8; 3]); 16] = [
+    (CellColor::Black, [0, 0, 0]),
+    (CellColor::Red, [205, 0, 0]),
+    (CellColor::Green, [0, 205, 0]),
+    (CellColor::Yellow, [205, 205, 0]),
+    (CellColor::Blue, [0, 0, 238]),
+    (CellColor::Magenta, [205, 0, 205]),
+    (CellColor::Cyan, [0, 205, 205]),
+    (CellColor::Gray, [229, 229, 229]),
+    (CellColor::DarkGray, [127, 127, 127]),
+    (CellColor::LightRed, [255, 0, 0]),
+    (CellColor::LightGreen, [0, 255, 0]),
+    (CellColor::LightYellow, [255, 255, 0]),
+    (CellColor::LightBlue, [92, 92, 255]),
+    (CellColor::LightMagenta, [255, 0, 255]),
+    (CellColor::LightCyan, [0, 255, 255]),
+    (CellColor::White, [255, 255, 255]),
+];
+
+/// The channel values of the xterm 6×6×6 color cube.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorDepth {
+    /// Detects the color depth of the terminal from the environment.
+    ///
+    /// See [`detect`](Self::detect) for the variables that are looked at.
+    pub fn from_env() -> ColorDepth {
+        ColorDepth::detect(|name| std::env::var(name).ok())
+    }
+
+    /// Detects the color depth of a terminal from its environment variables,
+    /// as returned by `var`.
+    ///
+    /// In order of precedence:
+    ///
+    /// - `FORCE_COLOR` is an explicit override: `0` or `false` disables
+    ///   colors, `1` or `true` selects 16 colors, `2` selects 256 colors and
+    ///   `3` selects true color.
+    /// - A non-empty `NO_COLOR` disables colors, see <https://no-color.org>.
+    /// - `COLORTERM=truecolor` or `COLORTERM=24bit` selects true color. Most
+    ///   terminal emulators set it, and it is forwarded by SSH and tmux when
+    ///   they are configured to.
+    /// - `TERM` is used last: `dumb` has no colors, names ending with
+    ///   `-direct` have true color, names containing `256color` (such as
+    ///   `xterm-256color` or `tmux-256color`) have 256 colors, and anything
+    ///   else, including the Linux console, is assumed to have 16 colors.
+    pub fn detect(var: impl Fn(&str) -> Option<String>) -> ColorDepth {
+        if let Some(force) = var("FORCE_COLOR") {
+            match force.trim() {
+                "0" | "false" => return ColorDepth::Monochrome,
+                "" | "1" | "true" => return ColorDepth::Ansi16,
+                "2" => return ColorDepth::Ansi256,
+                "3" => return ColorDepth::TrueColor,
+                _ => {}
+            }
+        }
+        if var("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            return ColorDepth::Monochrome;
+        }
+        if let Some(colorterm) = var("COLORTERM") {
+            if matches!(colorterm.trim(), "truecolor" | "24bit") {
+                return ColorDepth::TrueColor;
+            }
+        }
+        let term = var("TERM").unwrap_or_default();
+        if term == "dumb" {
+            ColorDepth::Monochrome
+        } else if term.ends_with("-direct") {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+
+    /// Maps an opaque `color` to the nearest color available at this depth.
+    ///
+    /// The alpha channel is ignored: translucent colors must be blended with
+    /// what is behind them first.
+    pub fn to_cell_color(self, color: Color) -> CellColor {
+        let rgb = [color.r, color.g, color.b];
+        match self {
+            ColorDepth::TrueColor => CellColor::Rgb(color.r, color.g, color.b),
+            ColorDepth::Ansi256 => CellColor::Indexed(nearest_256(rgb)),
+            ColorDepth::Ansi16 => {
+                ANSI_16
+                    .iter()
+                    .min_by_key(|(_, candidate)| distance(rgb, *candidate))
+                    .unwrap()
+                    .0
+            }
+            ColorDepth::Monochrome => CellColor::Reset,
+        }
+    }
+}
+
+/// The index of the xterm palette color nearest to `rgb`, ignoring the first
+/// 16 colors.
+fn nearest_256(rgb: [u8; 3]) -> u8 {
+    let level = |v: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| CUBE_LEVELS[i].abs_diff(v))
+            .unwrap()
+    };
+    let [r, g, b] = rgb.map(level);
+    let cube_index = 16 + 36 * r + 6 * g + b;
+    let cube = [CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]];
+
+    // The grayscale ramp goes from 8 to 238 in steps of 10.
+    let average = (u32::from(rgb[0]) + u32::from(rgb[1]) + u32::from(rgb[2])) / 3;
+    let step = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray_index = 232 + step;
+    let gray = [8 + 10 * step; 3];
+
+    if distance(rgb, gray) < distance(rgb, cube) {
+        gray_index
+    } else {
+        cube_index as u8
+    }
+}
+
+/// The squared euclidean distance between two colors.
+fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    a.iter()
+        .zip(b)
+        .map(|(&a, b)| u32::from(a.abs_diff(b)).pow(2))
+        .sum()
+}
+
+/// The relative luminance of `color`, between 0 and 1.
+pub(crate) fn luminance(color: Color) -> f64 {
+    (0.2126 * f64::from(color.r) + 0.7152 * f64::from(color.g) + 0.0722 * f64::from(color.b))
+        / 255.0
+}
+
+/// Composites `top` over the opaque color `bottom`.
+pub(crate) fn blend(bottom: Color, top: Color) -> Color {
+    let alpha = f32::from(top.a) / 255.0;
+    let mix = |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * alpha).round() as u8;
+    Color::rgb8(
+        mix(bottom.r, top.r),
+        mix(bottom.g, top.g),
+        mix(bottom.b, top.b),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(vars: &[(&str, &str)]) -> ColorDepth {
+        ColorDepth::detect(|name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn depth_is_detected_from_env() {
+        assert_eq!(detect(&[]), ColorDepth::Ansi16);
+        assert_eq!(detect(&[("TERM", "linux")]), ColorDepth::Ansi16);
+        assert_eq!(detect(&[("TERM", "dumb")]), ColorDepth::Monochrome);
+        assert_eq!(detect(&[("TERM", "tmux-256color")]), ColorDepth::Ansi256);
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color"), ("COLORTERM", "truecolor")]),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color"), ("NO_COLOR", "1")]),
+            ColorDepth::Monochrome
+        );
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color"), ("NO_COLOR", "")]),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(
+            detect(&[("NO_COLOR", "1"), ("FORCE_COLOR", "3")]),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            detect(&[("COLORTERM", "24bit"), ("FORCE_COLOR", "0")]),
+            ColorDepth::Monochrome
+        );
+    }
+
+    #[test]
+    fn colors_map_to_nearest_palette_entry() {
+        let color = Color::rgb8(0x29, 0x29, 0x29);
+        assert_eq!(
+            ColorDepth::TrueColor.to_cell_color(color),
+            CellColor::Rgb(0x29, 0x29, 0x29)
+        );
+        // Grays use the grayscale ramp.
+        assert_eq!(
+            ColorDepth::Ansi256.to_cell_color(color),
+            CellColor::Indexed(235)
+        );
+        assert_eq!(
+            ColorDepth::Ansi256.to_cell_color(Color::rgb8(255, 0, 0)),
+            CellColor::Indexed(196)
+        );
+        assert_eq!(
+            ColorDepth::Ansi256.to_cell_color(Color::rgb8(0, 0, 0)),
+            CellColor::Indexed(16)
+        );
+        assert_eq!(ColorDepth::Ansi16.to_cell_color(color), CellColor::Black);
+        assert_eq!(
+            ColorDepth::Ansi16.to_cell_color(Color::rgb8(0xf0, 0xf0, 0xea)),
+            CellColor::Gray
+        );
+        assert_eq!(
+            ColorDepth::Ansi16.to_cell_color(Color::rgb8(200, 30, 20)),
+            CellColor::Red
+        );
+        assert_eq!(
+            ColorDepth::Monochrome.to_cell_color(color),
+            CellColor::Reset
+        );
+    }
+
+    #[test]
+    fn translucent_colors_are_blended() {
+        let top = Color::rgba8(255, 255, 255, 128);
+        assert_eq!(blend(Color::BLACK, top), Color::rgb8(128, 128, 128));
+        assert_eq!(blend(Color::BLACK, Color::RED), Color::RED);
+        assert_eq!(blend(Color::RED, Color::TRANSPARENT), Color::RED);
+    }
+}
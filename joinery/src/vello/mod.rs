@@ -0,0 +1,646 @@
+pub use kurbo;
+pub use peniko;
+
+use kurbo::{Affine, BezPath, Line, Rect, RoundedRect, Shape, Stroke};
+use peniko::{BlendMode, Brush, BrushRef, Color, Fill, Font, Image, StyleRef};
+
+use self::glyph::{Glyph, GlyphRun};
+
+mod box_drawing;
+mod color;
+mod render;
+mod sub_cell;
+pub use color::ColorDepth;
+pub use render::{RenderParams, Renderer};
+pub use sub_cell::SubCellMode;
+
+/// The display list of a [`Scene`].
+///
+/// Unlike the real vello encoding, this is not a set of GPU streams: it is a
+/// flat list of [`Command`]s whose geometry has already been transformed into
+/// scene coordinates (logical pixels). This is the form a terminal rasterizer
+/// wants to consume, since it only ever needs to answer "what ends up in this
+/// cell?".
+#[derive(Clone, Default, Debug)]
+pub struct Encoding {
+    commands: Vec<Command>,
+}
+
+impl Encoding {
+    /// Returns the recorded drawing commands, in painting order.
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    /// Returns `true` if no command has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    fn reset(&mut self) {
+        self.commands.clear();
+    }
+
+    fn push(&mut self, command: Command) {
+        self.commands.push(command);
+    }
+
+    fn append(&mut self, other: &Encoding, transform: Option<Affine>) {
+        match transform {
+            Some(transform) if transform != Affine::IDENTITY => self.commands.extend(
+                other
+                    .commands
+                    .iter()
+                    .map(|command| command.transformed(transform)),
+            ),
+            _ => self.commands.extend_from_slice(&other.commands),
+        }
+    }
+}
+
+/// A single drawing operation recorded in a [`Scene`].
+///
+/// All geometry is expressed in scene coordinates: the transform passed to the
+/// drawing method (and to [`Scene::append`]) has already been applied.
+#[derive(Clone, Debug)]
+pub enum Command {
+    /// Fill the interior of a shape.
+    Fill {
+        shape: SceneShape,
+        /// Which points of a self-intersecting path are inside it.
+        style: Fill,
+        brush: Brush,
+        /// Maps brush space (e.g. gradient end points) to scene coordinates.
+        brush_transform: Affine,
+    },
+    /// Stroke the outline of a shape.
+    Stroke {
+        shape: SceneShape,
+        style: Stroke,
+        brush: Brush,
+        /// Maps brush space (e.g. gradient end points) to scene coordinates.
+        brush_transform: Affine,
+    },
+    /// Draw a span of text, one grapheme cluster at a time.
+    Text(TextSpan),
+    /// Draw a run of positioned glyphs.
+    ///
+    /// Glyph ids only have meaning for the font that produced them, so a
+    /// terminal can't do much with these; prefer [`Command::Text`].
+    Glyphs(GlyphRun),
+    /// Start a layer clipped to a shape. Matched by a later [`Command::PopLayer`].
+    PushLayer {
+        blend: BlendMode,
+        alpha: f32,
+        clip: SceneShape,
+    },
+    /// End the layer started by the matching [`Command::PushLayer`].
+    PopLayer,
+}
+
+impl Command {
+    fn transformed(&self, transform: Affine) -> Command {
+        match self {
+            Command::Fill {
+                shape,
+                style,
+                brush,
+                brush_transform,
+            } => Command::Fill {
+                shape: shape.transformed(transform),
+                style: *style,
+                brush: brush.clone(),
+                brush_transform: transform * *brush_transform,
+            },
+            Command::Stroke {
+                shape,
+                style,
+                brush,
+                brush_transform,
+            } => Command::Stroke {
+                shape: shape.transformed(transform),
+                style: style.clone(),
+                brush: brush.clone(),
+                brush_transform: transform * *brush_transform,
+            },
+            Command::Text(span) => Command::Text(span.transformed(transform)),
+            Command::Glyphs(run) => Command::Glyphs(GlyphRun {
+                transform: transform * run.transform,
+                ..run.clone()
+            }),
+            Command::PushLayer { blend, alpha, clip } => Command::PushLayer {
+                blend: *blend,
+                alpha: *alpha,
+                clip: clip.transformed(transform),
+            },
+            Command::PopLayer => Command::PopLayer,
+        }
+    }
+}
+
+/// A shape, classified into the kinds a terminal renders differently.
+///
+/// Rectangles, rounded rectangles and lines stay recognizable as long as the
+/// transform applied to them only translates and scales; anything else is
+/// flattened into a [`BezPath`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneShape {
+    Rect(Rect),
+    RoundedRect(RoundedRect),
+    Line(Line),
+    Path(BezPath),
+}
+
+impl SceneShape {
+    /// Classify `shape` and apply `transform` to it.
+    pub fn new(shape: &impl Shape, transform: Affine) -> SceneShape {
+        let shape = if let Some(rect) = shape.as_rect() {
+            SceneShape::Rect(rect)
+        } else if let Some(rounded_rect) = shape.as_rounded_rect() {
+            SceneShape::RoundedRect(rounded_rect)
+        } else if let Some(line) = shape.as_line() {
+            SceneShape::Line(line)
+        } else {
+            SceneShape::Path(shape.to_path(PATH_TOLERANCE))
+        };
+        shape.transformed(transform)
+    }
+
+    /// The smallest axis-aligned rectangle containing the shape.
+    pub fn bounding_box(&self) -> Rect {
+        match self {
+            SceneShape::Rect(rect) => *rect,
+            SceneShape::RoundedRect(rounded_rect) => rounded_rect.rect(),
+            SceneShape::Line(line) => line.bounding_box(),
+            SceneShape::Path(path) => path.bounding_box(),
+        }
+    }
+
+    fn transformed(&self, transform: Affine) -> SceneShape {
+        if transform == Affine::IDENTITY {
+            return self.clone();
+        }
+        let [a, b, c, d, _, _] = transform.as_coeffs();
+        let axis_aligned = b == 0.0 && c == 0.0;
+        match self {
+            SceneShape::Rect(rect) if axis_aligned => {
+                SceneShape::Rect(transform.transform_rect_bbox(*rect))
+            }
+            SceneShape::RoundedRect(rounded_rect) if axis_aligned && a.abs() == d.abs() => {
+                let rect = transform.transform_rect_bbox(rounded_rect.rect());
+                let radii = rounded_rect.radii();
+                let scale = a.abs();
+                SceneShape::RoundedRect(RoundedRect::from_rect(
+                    rect,
+                    (
+                        radii.top_left * scale,
+                        radii.top_right * scale,
+                        radii.bottom_right * scale,
+                        radii.bottom_left * scale,
+                    ),
+                ))
+            }
+            SceneShape::Line(line) => SceneShape::Line(transform * *line),
+            SceneShape::Path(path) => SceneShape::Path(transform * path),
+            shape => SceneShape::Path(transform * shape.to_path(PATH_TOLERANCE)),
+        }
+    }
+
+    fn to_path(&self, tolerance: f64) -> BezPath {
+        match self {
+            SceneShape::Rect(rect) => rect.to_path(tolerance),
+            SceneShape::RoundedRect(rounded_rect) => rounded_rect.to_path(tolerance),
+            SceneShape::Line(line) => line.to_path(tolerance),
+            SceneShape::Path(path) => path.clone(),
+        }
+    }
+}
+
+/// Tolerance used when flattening curves, in logical pixels.
+///
+/// Terminal cells are several pixels wide, so this can be coarse.
+const PATH_TOLERANCE: f64 = 0.1;
+
+/// A run of text laid out on a single line.
+///
+/// The span is positioned by the line box it sits in, rather than by its
+/// baseline: terminals draw every grapheme cluster in a full cell, so the
+/// vertical position within the line doesn't matter.
+#[derive(Clone, Debug)]
+pub struct TextSpan {
+    /// The text to draw.
+    pub text: String,
+    /// The line box of the span, in scene coordinates.
+    ///
+    /// The width is the advance of the span as measured by the layout.
+    pub rect: Rect,
+    /// The color of the text.
+    pub brush: Brush,
+    /// Whether the text is underlined.
+    pub underline: bool,
+    /// Whether the text is struck through.
+    pub strikethrough: bool,
+}
+
+impl TextSpan {
+    fn transformed(&self, transform: Affine) -> TextSpan {
+        TextSpan {
+            rect: transform.transform_rect_bbox(self.rect),
+            ..self.clone()
+        }
+    }
+}
+
+/// The main datatype for rendering graphics.
+///
+/// A Scene stores a sequence of drawing commands, their context, and the
+/// associated resources, which can later be rendered.
+#[derive(Clone, Default, Debug)]
+pub struct Scene {
+    encoding: Encoding,
+    /// Number of layers pushed and not yet popped.
+    layer_depth: usize,
+}
+
+impl Scene {
+    pub fn new() -> Scene {
+        Self::default()
+    }
+
+    /// Removes all content from the scene.
+    pub fn reset(&mut self) {
+        self.encoding.reset();
+        self.layer_depth = 0;
+    }
+
+    /// Returns the underlying raw encoding.
+    pub fn encoding(&self) -> &Encoding {
+        &self.encoding
+    }
+
+    /// Pushes a new layer clipped by the specified shape and composed with
+    /// previous layers using the specified blend mode.
+    ///
+    /// Every drawing command after this call will be clipped by the shape
+    /// until the layer is popped.
+    ///
+    /// **However, the transforms are *not* saved or modified by the layer stack.**
+    pub fn push_layer(
+        &mut self,
+        blend: impl Into<BlendMode>,
+        alpha: f32,
+        transform: Affine,
+        clip: &impl Shape,
+    ) {
+        self.layer_depth += 1;
+        self.encoding.push(Command::PushLayer {
+            blend: blend.into(),
+            alpha,
+            clip: SceneShape::new(clip, transform),
+        });
+    }
+
+    /// Pops the current layer.
+    pub fn pop_layer(&mut self) {
+        if self.layer_depth == 0 {
+            debug_panic!("Scene::pop_layer called without a matching push_layer");
+            return;
+        }
+        self.layer_depth -= 1;
+        self.encoding.push(Command::PopLayer);
+    }
+
+    /// Fills a shape using the specified style and brush.
+    pub fn fill<'b>(
+        &mut self,
+        style: Fill,
+        transform: Affine,
+        brush: impl Into<BrushRef<'b>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        let shape = SceneShape::new(shape, transform);
+        if shape.bounding_box().area() == 0.0 {
+            return;
+        }
+        self.encoding.push(Command::Fill {
+            shape,
+            style,
+            brush: brush.into().to_owned(),
+            brush_transform: transform * brush_transform.unwrap_or(Affine::IDENTITY),
+        });
+    }
+
+    /// Strokes a shape using the specified style and brush.
+    pub fn stroke<'b>(
+        &mut self,
+        style: &Stroke,
+        transform: Affine,
+        brush: impl Into<peniko::BrushRef<'b>>,
+        brush_transform: Option<Affine>,
+        shape: &impl Shape,
+    ) {
+        if style.width <= 0.0 {
+            return;
+        }
+        self.encoding.push(Command::Stroke {
+            shape: SceneShape::new(shape, transform),
+            style: style.clone(),
+            brush: brush.into().to_owned(),
+            brush_transform: transform * brush_transform.unwrap_or(Affine::IDENTITY),
+        });
+    }
+
+    /// Draws an image at its natural size with the given transform.
+    pub fn draw_image(&mut self, image: &Image, transform: Affine) {
+        self.fill(
+            Fill::NonZero,
+            transform,
+            image,
+            None,
+            &Rect::new(0.0, 0.0, image.width as f64, image.height as f64),
+        );
+    }
+
+    /// Draws a span of text occupying the line box `rect`.
+    ///
+    /// This has no vello equivalent: terminals render text as grapheme
+    /// clusters, not glyph outlines.
+    pub fn draw_text<'b>(
+        &mut self,
+        transform: Affine,
+        text: &str,
+        rect: Rect,
+        brush: impl Into<BrushRef<'b>>,
+    ) {
+        self.draw_text_span(
+            transform,
+            TextSpan {
+                text: text.to_string(),
+                rect,
+                brush: brush.into().to_owned(),
+                underline: false,
+                strikethrough: false,
+            },
+        );
+    }
+
+    /// Draws a span of text, with decorations.
+    ///
+    /// See [`Scene::draw_text`].
+    pub fn draw_text_span(&mut self, transform: Affine, span: TextSpan) {
+        if span.text.is_empty() {
+            return;
+        }
+        self.encoding
+            .push(Command::Text(span.transformed(transform)));
+    }
+
+    /// Returns a builder for encoding a glyph run.
+    pub fn draw_glyphs(&mut self, font: &Font) -> DrawGlyphs<'_> {
+        DrawGlyphs {
+            encoding: &mut self.encoding,
+            font: font.clone(),
+            transform: Affine::IDENTITY,
+            glyph_transform: None,
+            font_size: 16.0,
+            brush: Color::BLACK.into(),
+            brush_alpha: 1.0,
+        }
+    }
+
+    /// Appends a child scene.
+    ///
+    /// The given transform is applied to every transform in the child.
+    /// This is an O(N) operation.
+    pub fn append(&mut self, other: &Scene, transform: Option<Affine>) {
+        if other.layer_depth != 0 {
+            debug_panic!(
+                "Scene::append called with a scene that has {} unpopped layers",
+                other.layer_depth
+            );
+        }
+        self.encoding.append(&other.encoding, transform);
+    }
+}
+
+/// Builder for encoding a glyph run.
+pub struct DrawGlyphs<'a> {
+    encoding: &'a mut Encoding,
+    font: Font,
+    transform: Affine,
+    glyph_transform: Option<Affine>,
+    font_size: f32,
+    brush: BrushRef<'a>,
+    brush_alpha: f32,
+}
+
+impl<'a> DrawGlyphs<'a> {
+    /// Sets the global transform. This is applied to all glyphs after the offset
+    /// translation.
+    ///
+    /// The default value is the identity matrix.
+    pub fn transform(mut self, transform: Affine) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Sets the per-glyph transform. This is applied to all glyphs prior to
+    /// offset translation. This is common used for applying a shear to simulate
+    /// an oblique font.
+    ///
+    /// The default value is `None`.
+    pub fn glyph_transform(mut self, transform: Option<Affine>) -> Self {
+        self.glyph_transform = transform;
+        self
+    }
+
+    /// Sets the font size in pixels per em units.
+    ///
+    /// The default value is 16.0.
+    pub fn font_size(mut self, size: f32) -> Self {
+        self.font_size = size;
+        self
+    }
+
+    /// Sets the brush.
+    ///
+    /// The default value is solid black.
+    pub fn brush(mut self, brush: impl Into<BrushRef<'a>>) -> Self {
+        self.brush = brush.into();
+        self
+    }
+
+    /// Sets an additional alpha multiplier for the brush.
+    ///
+    /// The default value is 1.0.
+    pub fn brush_alpha(mut self, alpha: f32) -> Self {
+        self.brush_alpha = alpha;
+        self
+    }
+
+    /// Encodes a fill or stroke for the given sequence of glyphs and consumes the builder.
+    ///
+    /// The `style` parameter accepts either `Fill` or `&Stroke` types.
+    pub fn draw(self, _style: impl Into<StyleRef<'a>>, glyphs: impl Iterator<Item = Glyph>) {
+        let glyphs: Vec<Glyph> = glyphs.collect();
+        if glyphs.is_empty() {
+            return;
+        }
+        self.encoding.push(Command::Glyphs(GlyphRun {
+            font: self.font,
+            transform: self.transform,
+            glyph_transform: self.glyph_transform,
+            font_size: self.font_size,
+            brush: self.brush.to_owned(),
+            brush_alpha: self.brush_alpha,
+            glyphs,
+        }));
+    }
+}
+
+pub mod glyph {
+    use kurbo::Affine;
+    use peniko::{Brush, Font};
+
+    /// Positioned glyph.
+    #[derive(Copy, Clone, Default, Debug)]
+    pub struct Glyph {
+        /// Glyph identifier.
+        pub id: u32,
+        /// X-offset in run, relative to transform.
+        pub x: f32,
+        /// Y-offset in run, relative to transform.
+        pub y: f32,
+    }
+
+    /// Properties for a sequence of glyphs in an encoding.
+    #[derive(Clone, Debug)]
+    pub struct GlyphRun {
+        /// Font for all glyphs in the run.
+        pub font: Font,
+        /// Global run transform.
+        pub transform: Affine,
+        /// Per-glyph transform.
+        pub glyph_transform: Option<Affine>,
+        /// Size of the font in pixels per em.
+        pub font_size: f32,
+        /// Brush used for every glyph in the run.
+        pub brush: Brush,
+        /// Alpha multiplier applied to the brush.
+        pub brush_alpha: f32,
+        /// The glyphs of the run.
+        pub glyphs: Vec<Glyph>,
+    }
+}
+
+pub struct AaSupport {}
+pub struct RendererOptions {}
+pub enum AaConfig {
+    Area,
+    Msaa8,
+    Msaa16,
+}
+
+pub mod util {
+    pub struct RenderContext {}
+    /// Combination of surface and its configuration.
+    #[derive(Debug)]
+    pub struct RenderSurface<'s> {
+        pub surface: ratatui::terminal::Frame<'s>,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_translates_commands() {
+        let mut child = Scene::new();
+        child.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            Color::RED,
+            None,
+            &Rect::new(0.0, 0.0, 10.0, 5.0),
+        );
+        child.draw_text(
+            Affine::IDENTITY,
+            "hi",
+            Rect::new(0.0, 0.0, 16.0, 16.0),
+            Color::WHITE,
+        );
+
+        let mut scene = Scene::new();
+        scene.append(&child, Some(Affine::translate((8.0, 16.0))));
+
+        let commands = scene.encoding().commands();
+        assert_eq!(commands.len(), 2);
+        match &commands[0] {
+            Command::Fill { shape, .. } => {
+                assert_eq!(*shape, SceneShape::Rect(Rect::new(8.0, 16.0, 18.0, 21.0)));
+            }
+            command => panic!("unexpected command {command:?}"),
+        }
+        match &commands[1] {
+            Command::Text(span) => {
+                assert_eq!(span.text, "hi");
+                assert_eq!(span.rect, Rect::new(8.0, 16.0, 24.0, 32.0));
+            }
+            command => panic!("unexpected command {command:?}"),
+        }
+    }
+
+    #[test]
+    fn shapes_are_classified() {
+        let rounded_rect = Rect::new(0.0, 0.0, 10.0, 10.0).to_rounded_rect(2.0);
+        let shape = SceneShape::new(&rounded_rect, Affine::scale(2.0));
+        assert_eq!(
+            shape,
+            SceneShape::RoundedRect(Rect::new(0.0, 0.0, 20.0, 20.0).to_rounded_rect(4.0))
+        );
+
+        let rotated = SceneShape::new(&Rect::new(0.0, 0.0, 10.0, 10.0), Affine::rotate(0.5));
+        assert!(matches!(rotated, SceneShape::Path(_)));
+
+        let line = SceneShape::new(
+            &Line::new((0.0, 0.0), (4.0, 0.0)),
+            Affine::translate((1.0, 1.0)),
+        );
+        assert_eq!(line, SceneShape::Line(Line::new((1.0, 1.0), (5.0, 1.0))));
+    }
+
+    #[test]
+    fn empty_fill_is_dropped() {
+        let mut scene = Scene::new();
+        scene.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            Color::TRANSPARENT,
+            None,
+            &Rect::ZERO,
+        );
+        assert!(scene.encoding().is_empty());
+    }
+
+    #[test]
+    fn layers_are_recorded() {
+        let mut scene = Scene::new();
+        scene.push_layer(
+            BlendMode::default(),
+            0.5,
+            Affine::translate((1.0, 0.0)),
+            &Rect::new(0.0, 0.0, 4.0, 4.0),
+        );
+        scene.pop_layer();
+
+        let commands = scene.encoding().commands();
+        assert!(matches!(
+            &commands[0],
+            Command::PushLayer { alpha, clip: SceneShape::Rect(rect), .. }
+                if *alpha == 0.5 && *rect == Rect::new(1.0, 0.0, 5.0, 4.0)
+        ));
+        assert!(matches!(commands[1], Command::PopLayer));
+    }
+}
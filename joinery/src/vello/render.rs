@@ -0,0 +1,1188 @@
+//! Rasterization of a [`Scene`] into a ratatui [`Buffer`].
+//!
+//! The renderer walks the display list once, in painting order, and decides
+//! for each command which cells it touches, following the snapping rules of
+//! [`CellMetrics`]. Colors are composited at full precision, and only
+//! reduced to the [`ColorDepth`] of the terminal once every command has been
+//! rasterized.
+
+use std::ops::Range;
+
+use kurbo::{BezPath, Point, Rect, Shape, Stroke, StrokeOpts};
+use peniko::{Brush, Color, Extend, Fill, Gradient, GradientKind, Image};
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::layout;
+use ratatui::style::Modifier;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::box_drawing::{Arms, LineWeight};
+use super::color::{blend, luminance};
+use super::{ColorDepth, Command, Scene, SceneShape, SubCellMode, TextSpan};
+use crate::CellMetrics;
+
+/// Parameters used in a single render that are configurable by the client.
+#[derive(Clone, Debug)]
+pub struct RenderParams {
+    /// The background color applied to the target before any commands are
+    /// rasterized.
+    pub base_color: Color,
+    /// The size of a cell in scene coordinates.
+    pub cell_metrics: CellMetrics,
+    /// The colors the terminal can display.
+    pub color_depth: ColorDepth,
+    /// How shapes finer than a cell are drawn.
+    pub sub_cell_mode: SubCellMode,
+}
+
+/// Rasterizes scenes into terminal cells.
+#[derive(Debug, Default)]
+pub struct Renderer {
+    /// Scratch storage for the state of the target cells.
+    cells: Vec<CellState>,
+    /// Scratch storage for the cells to redraw, see
+    /// [`render_damage_to_buffer`](Self::render_damage_to_buffer).
+    damaged_cells: Vec<bool>,
+}
+
+/// What the renderer keeps track of for each cell, on top of its symbol.
+#[derive(Clone, Copy, Debug)]
+struct CellState {
+    /// The color of the symbol, or `None` if the cell has no visible symbol.
+    /// Colors are reduced to the terminal's color depth at the very end.
+    fg: Option<Color>,
+    /// The background color. Always opaque.
+    bg: Color,
+    /// The box-drawing lines in the cell, so that strokes crossing it can be
+    /// merged into a junction.
+    lines: Arms,
+    /// The sub-pixels set in the cell by shapes, so that shapes sharing the
+    /// cell can be merged. See [`SubCellMode::bit`].
+    dots: u8,
+}
+
+impl Renderer {
+    pub fn new() -> Renderer {
+        Renderer::default()
+    }
+
+    /// Renders `scene` into the `area` of `buffer`.
+    ///
+    /// The scene origin is placed at the top-left corner of `area`, and
+    /// nothing is written outside of it.
+    pub fn render_to_buffer(
+        &mut self,
+        scene: &Scene,
+        buffer: &mut Buffer,
+        area: layout::Rect,
+        params: &RenderParams,
+    ) {
+        self.render(scene, buffer, area, None, params);
+    }
+
+    /// Renders the parts of `scene` that intersect `damage` into the `area`
+    /// of `buffer`.
+    ///
+    /// `damage` is a list of rects in scene coordinates, such as the ones
+    /// returned by [`RenderRoot::take_damage`](crate::render_root::RenderRoot::take_damage).
+    /// Every cell they touch is redrawn, and every other cell is left as it
+    /// is. `buffer` must hold the previous frame, so that only the damaged
+    /// cells differ from it: the terminal then only has to be sent these.
+    pub fn render_damage_to_buffer(
+        &mut self,
+        scene: &Scene,
+        buffer: &mut Buffer,
+        area: layout::Rect,
+        damage: &[Rect],
+        params: &RenderParams,
+    ) {
+        let area = area.intersection(buffer.area);
+        let mut damaged_cells = std::mem::take(&mut self.damaged_cells);
+        damaged_cells.clear();
+        damaged_cells.resize(area.area() as usize, false);
+        for rect in damage {
+            let (cols, rows) = params.cell_metrics.cells_touching(*rect);
+            let cols = cols.start.max(0)..cols.end.min(area.width.into());
+            let rows = rows.start.max(0)..rows.end.min(area.height.into());
+            for row in rows {
+                for col in cols.clone() {
+                    damaged_cells[row as usize * usize::from(area.width) + col as usize] = true;
+                }
+            }
+        }
+        self.render(scene, buffer, area, Some(&damaged_cells), params);
+        self.damaged_cells = damaged_cells;
+    }
+
+    fn render(
+        &mut self,
+        scene: &Scene,
+        buffer: &mut Buffer,
+        area: layout::Rect,
+        damaged_cells: Option<&[bool]>,
+        params: &RenderParams,
+    ) {
+        let area = area.intersection(buffer.area);
+        let base_color = blend(Color::BLACK, params.base_color);
+        let mut target = Target {
+            buffer,
+            area,
+            metrics: params.cell_metrics,
+            sub_cell_mode: params.sub_cell_mode,
+            cells: &mut self.cells,
+            damaged_cells,
+            layers: Vec::new(),
+        };
+        target.clear(base_color);
+
+        for command in scene.encoding().commands() {
+            match command {
+                Command::Fill {
+                    shape,
+                    style,
+                    brush,
+                    brush_transform,
+                } => {
+                    let paint = Paint::new(brush, *brush_transform);
+                    target.fill(shape, *style, &paint);
+                }
+                Command::Stroke {
+                    shape,
+                    style,
+                    brush,
+                    brush_transform,
+                } => {
+                    let paint = Paint::new(brush, *brush_transform);
+                    target.stroke(shape, style, &paint);
+                }
+                Command::Text(span) => target.text(span),
+                // Glyph ids can't be turned back into text.
+                Command::Glyphs(_) => {}
+                // Only normal blending can be done with whole cells.
+                Command::PushLayer { alpha, clip, .. } => target.push_layer(clip, *alpha),
+                Command::PopLayer => target.pop_layer(),
+            }
+        }
+        target.finish(params.color_depth, base_color);
+    }
+}
+
+/// The part of a buffer a scene is rendered into.
+struct Target<'a> {
+    buffer: &'a mut Buffer,
+    area: layout::Rect,
+    metrics: CellMetrics,
+    sub_cell_mode: SubCellMode,
+    /// The state of the cells of `area`, row by row.
+    cells: &'a mut Vec<CellState>,
+    /// The cells of `area` that are redrawn, row by row, or `None` if all of
+    /// them are.
+    damaged_cells: Option<&'a [bool]>,
+    /// The stack of layers currently pushed.
+    layers: Vec<Layer>,
+}
+
+/// A layer started by [`Command::PushLayer`].
+struct Layer {
+    /// The columns writes are clipped to, intersected with the parent layer.
+    cols: Range<i64>,
+    /// The rows writes are clipped to, intersected with the parent layer.
+    rows: Range<i64>,
+    alpha: f32,
+    /// The clipped cells as they were before the layer, row by row, if the
+    /// layer has to be blended with them.
+    backdrop: Vec<(Cell, CellState)>,
+}
+
+impl Target<'_> {
+    fn clear(&mut self, color: Color) {
+        for y in self.area.top()..self.area.bottom() {
+            for x in self.area.left()..self.area.right() {
+                if self.is_damaged(x, y) {
+                    self.buffer.get_mut(x, y).reset();
+                }
+            }
+        }
+        self.cells.clear();
+        self.cells.resize(
+            self.area.area() as usize,
+            CellState {
+                fg: None,
+                bg: color,
+                lines: Arms::default(),
+                dots: 0,
+            },
+        );
+    }
+
+    /// Writes the colors of every cell to the buffer, reduced to `depth`.
+    ///
+    /// Without colors, backgrounds that stand out from `base_color` (such as
+    /// selections) are shown in reverse video instead.
+    fn finish(&mut self, depth: ColorDepth, base_color: Color) {
+        for (index, state) in self.cells.iter().enumerate() {
+            let x = self.area.x + (index % usize::from(self.area.width)) as u16;
+            let y = self.area.y + (index / usize::from(self.area.width)) as u16;
+            if !self.is_damaged(x, y) {
+                continue;
+            }
+            let cell = self.buffer.get_mut(x, y);
+            let fg = state.fg.unwrap_or(state.bg);
+            cell.set_fg(depth.to_cell_color(fg))
+                .set_bg(depth.to_cell_color(state.bg));
+            if depth == ColorDepth::Monochrome
+                && (luminance(state.bg) - luminance(base_color)).abs() > 0.25
+            {
+                cell.modifier.insert(Modifier::REVERSED);
+            }
+        }
+    }
+
+    /// Whether the cell at `x` and `y` in the buffer is redrawn.
+    fn is_damaged(&self, x: u16, y: u16) -> bool {
+        let index = usize::from(y - self.area.y) * usize::from(self.area.width)
+            + usize::from(x - self.area.x);
+        match self.damaged_cells {
+            Some(damaged) => damaged[index],
+            None => true,
+        }
+    }
+
+    /// Returns the index of the cell at column `col` and row `row` of the
+    /// area, if any.
+    ///
+    /// Cells outside of the current layer's clip, or that aren't redrawn, are
+    /// treated as missing.
+    fn index(&self, col: i64, row: i64) -> Option<usize> {
+        if col < 0 || row < 0 || col >= self.area.width.into() || row >= self.area.height.into() {
+            return None;
+        }
+        if let Some(layer) = self.layers.last() {
+            if !layer.cols.contains(&col) || !layer.rows.contains(&row) {
+                return None;
+            }
+        }
+        let index = row as usize * usize::from(self.area.width) + col as usize;
+        if self.damaged_cells.is_some_and(|damaged| !damaged[index]) {
+            return None;
+        }
+        Some(index)
+    }
+
+    /// Returns the cell at column `col` and row `row` of the area, if any.
+    fn cell(&mut self, col: i64, row: i64) -> Option<&mut Cell> {
+        self.index(col, row)?;
+        let x = self.area.x + col as u16;
+        let y = self.area.y + row as u16;
+        Some(self.buffer.get_mut(x, y))
+    }
+
+    /// Restricts writes to the cells whose centers lie inside the bounding
+    /// box of `clip`, until the matching [`pop_layer`](Self::pop_layer).
+    fn push_layer(&mut self, clip: &SceneShape, alpha: f32) {
+        // Non-rectangular clips are approximated by their bounding box.
+        let (cols, rows) = self.metrics.cell_range(clip.bounding_box());
+        let (parent_cols, parent_rows) = match self.layers.last() {
+            Some(parent) => (parent.cols.clone(), parent.rows.clone()),
+            None => (0..self.area.width.into(), 0..self.area.height.into()),
+        };
+        let intersect = |a: Range<i64>, b: Range<i64>| {
+            let start = a.start.max(b.start);
+            start..a.end.min(b.end).max(start)
+        };
+        let cols = intersect(cols, parent_cols);
+        let rows = intersect(rows, parent_rows);
+
+        let mut backdrop = Vec::new();
+        if alpha < 1.0 {
+            for row in rows.clone() {
+                for col in cols.clone() {
+                    let Some(index) = self.index(col, row) else {
+                        continue;
+                    };
+                    let cell = self.cell(col, row).unwrap().clone();
+                    backdrop.push((cell, self.cells[index]));
+                }
+            }
+        }
+        self.layers.push(Layer {
+            cols,
+            rows,
+            alpha,
+            backdrop,
+        });
+    }
+
+    /// Ends the current layer, blending it with its backdrop if it is
+    /// translucent.
+    ///
+    /// Symbols can't be blended: a cell keeps the symbol of whichever of the
+    /// layer and the backdrop is the most opaque.
+    fn pop_layer(&mut self) {
+        let Some(layer) = self.layers.pop() else {
+            return;
+        };
+        let alpha = layer.alpha.clamp(0.0, 1.0);
+        let mut backdrop = layer.backdrop.into_iter();
+        for row in layer.rows {
+            for col in layer.cols.clone() {
+                let Some(index) = self.index(col, row) else {
+                    continue;
+                };
+                let Some((saved_cell, saved)) = backdrop.next() else {
+                    return;
+                };
+                let current = self.cells[index];
+                let bg = lerp(saved.bg, current.bg, alpha);
+                if alpha >= 0.5 {
+                    self.cells[index] = CellState {
+                        fg: current.fg.map(|fg| lerp(saved.bg, fg, alpha)),
+                        bg,
+                        lines: current.lines,
+                        dots: current.dots,
+                    };
+                } else {
+                    self.cells[index] = CellState {
+                        fg: saved.fg.map(|fg| lerp(fg, current.bg, alpha)),
+                        bg,
+                        lines: saved.lines,
+                        dots: saved.dots,
+                    };
+                    *self.cell(col, row).unwrap() = saved_cell;
+                }
+            }
+        }
+    }
+
+    fn fill(&mut self, shape: &SceneShape, style: Fill, paint: &Paint) {
+        if let SceneShape::Path(path) = shape {
+            if self.sub_cell_mode != SubCellMode::Off {
+                self.fill_sub_cells(path, style, paint);
+                return;
+            }
+        }
+        let (cols, rows) = self.metrics.cell_range(shape.bounding_box());
+        let cols = cols.start.max(0)..cols.end.min(self.area.width.into());
+        let rows = rows.start.max(0)..rows.end.min(self.area.height.into());
+        for row in rows {
+            for col in cols.clone() {
+                let center = self.metrics.cell_center(col, row);
+                if !contains(shape, style, center) {
+                    continue;
+                }
+                if let Some(color) = paint.color_at(center) {
+                    self.fill_cell(col, row, color);
+                }
+            }
+        }
+    }
+
+    /// Fills a whole cell with `color`.
+    fn fill_cell(&mut self, col: i64, row: i64, color: Color) {
+        let Some(index) = self.index(col, row) else {
+            return;
+        };
+        let state = &mut self.cells[index];
+        if color.a == u8::MAX {
+            *state = CellState {
+                fg: None,
+                bg: color,
+                lines: Arms::default(),
+                dots: 0,
+            };
+            let cell = self.cell(col, row).unwrap();
+            cell.set_symbol(" ");
+            cell.modifier = Modifier::empty();
+        } else {
+            // Translucent fills tint what is already there.
+            state.bg = blend(state.bg, color);
+            state.fg = state.fg.map(|fg| blend(fg, color));
+        }
+    }
+
+    /// Fills `path` at the resolution of the [`SubCellMode`].
+    ///
+    /// A sub-pixel is set when at least half of it is covered, which picks
+    /// the character closest to the actual coverage of the cell.
+    fn fill_sub_cells(&mut self, path: &BezPath, style: Fill, paint: &Paint) {
+        const SAMPLES: [(f64, f64); 4] = [(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)];
+        let mode = self.sub_cell_mode;
+        let (sub_cols, sub_rows) = mode.grid();
+        let (cols, rows) = self.metrics.cells_touching(path.bounding_box());
+        let cols = cols.start.max(0)..cols.end.min(self.area.width.into());
+        let rows = rows.start.max(0)..rows.end.min(self.area.height.into());
+        for row in rows {
+            for col in cols.clone() {
+                let mut mask = 0;
+                for sub_row in 0..sub_rows {
+                    for sub_col in 0..sub_cols {
+                        let covered = SAMPLES
+                            .iter()
+                            .filter(|(dx, dy)| {
+                                let x =
+                                    col as f64 + (f64::from(sub_col) + dx) / f64::from(sub_cols);
+                                let y =
+                                    row as f64 + (f64::from(sub_row) + dy) / f64::from(sub_rows);
+                                let point =
+                                    Point::new(x * self.metrics.width, y * self.metrics.height);
+                                path_contains(path, style, point)
+                            })
+                            .count();
+                        if covered * 2 >= SAMPLES.len() {
+                            mask |= mode.bit(sub_col, sub_row);
+                        }
+                    }
+                }
+                if mask == 0 {
+                    continue;
+                }
+                if let Some(color) = paint.color_at(self.metrics.cell_center(col, row)) {
+                    self.put_dots(col, row, mask, color);
+                }
+            }
+        }
+    }
+
+    /// Adds sub-pixels to a cell, merging them with the ones already there.
+    fn put_dots(&mut self, col: i64, row: i64, mask: u8, color: Color) {
+        let Some(index) = self.index(col, row) else {
+            return;
+        };
+        let mode = self.sub_cell_mode;
+        let mask = mask | self.cells[index].dots;
+        if mask == mode.full_mask() {
+            self.fill_cell(col, row, color);
+            return;
+        }
+        let state = &mut self.cells[index];
+        state.fg = Some(blend(state.bg, color));
+        state.lines = Arms::default();
+        state.dots = mask;
+        let symbol = mode.symbol(mask);
+        self.cell(col, row)
+            .unwrap()
+            .set_symbol(symbol.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Strokes `path` at the resolution of the [`SubCellMode`].
+    fn stroke_sub_cells(&mut self, path: &BezPath, style: &Stroke, paint: &Paint) {
+        if self.sub_cell_mode == SubCellMode::Off {
+            return;
+        }
+        let (sub_cols, sub_rows) = self.sub_cell_mode.grid();
+        // Thin strokes are widened to a sub-pixel, so that they stay visible.
+        let sub_pixel = (self.metrics.width / f64::from(sub_cols))
+            .min(self.metrics.height / f64::from(sub_rows));
+        let mut style = style.clone();
+        style.width = style.width.max(sub_pixel);
+        let outline = kurbo::stroke(path, &style, &StrokeOpts::default(), sub_pixel / 10.0);
+        // Outlines overlap themselves where the path turns.
+        self.fill_sub_cells(&outline, Fill::NonZero, paint);
+    }
+
+    fn stroke(&mut self, shape: &SceneShape, style: &Stroke, paint: &Paint) {
+        let weight = LineWeight::for_stroke_width(style.width);
+        match shape {
+            SceneShape::Rect(rect) => self.stroke_box(*rect, weight, false, paint),
+            SceneShape::RoundedRect(rounded_rect) => {
+                let radii = rounded_rect.radii();
+                let rounded = radii.top_left > 0.0
+                    || radii.top_right > 0.0
+                    || radii.bottom_right > 0.0
+                    || radii.bottom_left > 0.0;
+                self.stroke_box(rounded_rect.rect(), weight, rounded, paint);
+            }
+            SceneShape::Line(line) => {
+                let (cols, rows) = self
+                    .metrics
+                    .cells_touching(Rect::from_points(line.p0, line.p1));
+                if line.p0.y == line.p1.y {
+                    for col in cols {
+                        self.put_lines(col, rows.start, Arms::horizontal(weight), paint);
+                    }
+                } else if line.p0.x == line.p1.x {
+                    for row in rows {
+                        self.put_lines(cols.start, row, Arms::vertical(weight), paint);
+                    }
+                } else {
+                    self.stroke_sub_cells(&line.to_path(0.1), style, paint);
+                }
+            }
+            SceneShape::Path(path) => self.stroke_sub_cells(path, style, paint),
+        }
+    }
+
+    /// Draws the outline of `rect` with box-drawing characters.
+    fn stroke_box(&mut self, rect: Rect, weight: LineWeight, rounded: bool, paint: &Paint) {
+        let (cols, rows) = self.metrics.cells_touching(rect);
+        let (left, right) = (cols.start, cols.end - 1);
+        let (top, bottom) = (rows.start, rows.end - 1);
+        if left == right || top == bottom {
+            // Too thin to have corners: draw a line instead.
+            let line = if left == right {
+                Arms::vertical(weight)
+            } else {
+                Arms::horizontal(weight)
+            };
+            for row in rows {
+                for col in cols.clone() {
+                    self.put_lines(col, row, line, paint);
+                }
+            }
+            return;
+        }
+        for col in left + 1..right {
+            self.put_lines(col, top, Arms::horizontal(weight), paint);
+            self.put_lines(col, bottom, Arms::horizontal(weight), paint);
+        }
+        for row in top + 1..bottom {
+            self.put_lines(left, row, Arms::vertical(weight), paint);
+            self.put_lines(right, row, Arms::vertical(weight), paint);
+        }
+        let corner = |vertical: bool, horizontal: bool| {
+            let (up, down) = if vertical {
+                (weight, LineWeight::None)
+            } else {
+                (LineWeight::None, weight)
+            };
+            let (left, right) = if horizontal {
+                (weight, LineWeight::None)
+            } else {
+                (LineWeight::None, weight)
+            };
+            Arms {
+                up,
+                right,
+                down,
+                left,
+                rounded,
+            }
+        };
+        self.put_lines(left, top, corner(false, false), paint);
+        self.put_lines(right, top, corner(false, true), paint);
+        self.put_lines(left, bottom, corner(true, false), paint);
+        self.put_lines(right, bottom, corner(true, true), paint);
+    }
+
+    /// Adds box-drawing lines to a cell, merging them with the lines that
+    /// are already there.
+    fn put_lines(&mut self, col: i64, row: i64, arms: Arms, paint: &Paint) {
+        let Some(index) = self.index(col, row) else {
+            return;
+        };
+        let lines = self.cells[index].lines.merge(arms);
+        let Some(symbol) = lines.symbol() else {
+            return;
+        };
+        if self.put(col, row, symbol.encode_utf8(&mut [0; 4]), paint) {
+            self.cells[index].lines = lines;
+        }
+    }
+
+    /// Writes `symbol` in the foreground of a cell, keeping its background.
+    ///
+    /// Returns `false` if nothing was written.
+    fn put(&mut self, col: i64, row: i64, symbol: &str, paint: &Paint) -> bool {
+        let Some(color) = paint.color_at(self.metrics.cell_center(col, row)) else {
+            return false;
+        };
+        let Some(index) = self.index(col, row) else {
+            return false;
+        };
+        let state = &mut self.cells[index];
+        state.fg = Some(blend(state.bg, color));
+        state.lines = Arms::default();
+        state.dots = 0;
+        self.cell(col, row).unwrap().set_symbol(symbol);
+        true
+    }
+
+    fn text(&mut self, span: &TextSpan) {
+        let paint = Paint::new(&span.brush, kurbo::Affine::IDENTITY);
+        let start = self
+            .metrics
+            .to_cells(Point::new(span.rect.x0, span.rect.center().y));
+        let row = start.y.floor() as i64;
+        let mut col = start.x.round() as i64;
+        let mut modifier = Modifier::empty();
+        if span.underline {
+            modifier |= Modifier::UNDERLINED;
+        }
+        if span.strikethrough {
+            modifier |= Modifier::CROSSED_OUT;
+        }
+        for grapheme in span.text.graphemes(true) {
+            let width = grapheme.width() as i64;
+            if width == 0 || grapheme.chars().all(char::is_control) {
+                continue;
+            }
+            // A wide grapheme cut by the clip would spill out of it.
+            if (col..col + width).any(|col| self.index(col, row).is_none()) {
+                col += width;
+                continue;
+            }
+            self.put(col, row, grapheme, &paint);
+            if let Some(cell) = self.cell(col, row) {
+                cell.modifier.insert(modifier);
+            }
+            // The cells hidden by a wide grapheme must not draw anything.
+            for hidden in col + 1..col + width {
+                if let Some(cell) = self.cell(hidden, row) {
+                    cell.set_symbol(" ");
+                }
+            }
+            col += width;
+        }
+    }
+}
+
+fn contains(shape: &SceneShape, style: Fill, point: Point) -> bool {
+    match shape {
+        SceneShape::Rect(rect) => rect.contains(point),
+        SceneShape::RoundedRect(rounded_rect) => rounded_rect.contains(point),
+        SceneShape::Line(_) => false,
+        SceneShape::Path(path) => path_contains(path, style, point),
+    }
+}
+
+/// Whether `point` is inside `path`, following the fill rule `style`.
+fn path_contains(path: &BezPath, style: Fill, point: Point) -> bool {
+    let winding = path.winding(point);
+    match style {
+        Fill::NonZero => winding != 0,
+        Fill::EvenOdd => winding % 2 != 0,
+    }
+}
+
+/// A brush resolved to the color of individual cells.
+struct Paint<'a> {
+    brush: &'a Brush,
+    /// Maps scene coordinates back to brush space.
+    inverse_transform: kurbo::Affine,
+}
+
+impl<'a> Paint<'a> {
+    fn new(brush: &'a Brush, brush_transform: kurbo::Affine) -> Paint<'a> {
+        Paint {
+            brush,
+            inverse_transform: brush_transform.inverse(),
+        }
+    }
+
+    /// The color of the brush at `point`, or `None` if it is fully transparent.
+    fn color_at(&self, point: Point) -> Option<Color> {
+        let color = match self.brush {
+            Brush::Solid(color) => *color,
+            Brush::Gradient(gradient) => sample_gradient(gradient, self.inverse_transform * point),
+            Brush::Image(image) => sample_image(image, self.inverse_transform * point),
+        };
+        (color.a != 0).then_some(color)
+    }
+}
+
+fn sample_gradient(gradient: &Gradient, point: Point) -> Color {
+    let t = match gradient.kind {
+        GradientKind::Linear { start, end } => {
+            let direction = end - start;
+            let length = direction.hypot2();
+            if length == 0.0 {
+                0.0
+            } else {
+                (point - start).dot(direction) / length
+            }
+        }
+        GradientKind::Radial {
+            end_center,
+            end_radius,
+            ..
+        } => {
+            if end_radius == 0.0 {
+                0.0
+            } else {
+                (point - end_center).hypot() / f64::from(end_radius)
+            }
+        }
+        GradientKind::Sweep {
+            center,
+            start_angle,
+            end_angle,
+        } => {
+            let angle = (point - center).atan2().rem_euclid(std::f64::consts::TAU);
+            let sweep = f64::from(end_angle - start_angle);
+            if sweep == 0.0 {
+                0.0
+            } else {
+                (angle - f64::from(start_angle)) / sweep
+            }
+        }
+    };
+    let t = extend(gradient.extend, t) as f32;
+
+    let stops = &gradient.stops;
+    let Some(first) = stops.first() else {
+        return Color::TRANSPARENT;
+    };
+    if t <= first.offset {
+        return first.color;
+    }
+    for pair in stops.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        if t <= b.offset {
+            let range = b.offset - a.offset;
+            let f = if range == 0.0 {
+                1.0
+            } else {
+                (t - a.offset) / range
+            };
+            return lerp(a.color, b.color, f);
+        }
+    }
+    stops[stops.len() - 1].color
+}
+
+fn sample_image(image: &Image, point: Point) -> Color {
+    if image.width == 0 || image.height == 0 {
+        return Color::TRANSPARENT;
+    }
+    let x = extend(image.extend, point.x / f64::from(image.width));
+    let y = extend(image.extend, point.y / f64::from(image.height));
+    let x = ((x * f64::from(image.width)) as u32).min(image.width - 1);
+    let y = ((y * f64::from(image.height)) as u32).min(image.height - 1);
+    // Images are always `Format::Rgba8`.
+    let offset = (y as usize * image.width as usize + x as usize) * 4;
+    match image.data.data().get(offset..offset + 4) {
+        Some(&[r, g, b, a]) => Color::rgba8(r, g, b, a),
+        _ => Color::TRANSPARENT,
+    }
+}
+
+/// Maps `t` into `0.0..=1.0` according to `extend`.
+fn extend(extend: Extend, t: f64) -> f64 {
+    match extend {
+        Extend::Pad => t.clamp(0.0, 1.0),
+        Extend::Repeat => t.rem_euclid(1.0),
+        Extend::Reflect => {
+            let t = t.rem_euclid(2.0);
+            if t > 1.0 {
+                2.0 - t
+            } else {
+                t
+            }
+        }
+    }
+}
+
+fn lerp(a: Color, b: Color, t: f32) -> Color {
+    let mix = |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * t).round() as u8;
+    Color::rgba8(mix(a.r, b.r), mix(a.g, b.g), mix(a.b, b.b), mix(a.a, b.a))
+}
+
+#[cfg(test)]
+mod tests {
+    use kurbo::{Affine, Circle, Line, RoundedRect};
+    use peniko::{BlendMode, Fill};
+
+    use super::*;
+
+    const BASE: Color = Color::BLACK;
+
+    fn render(scene: &Scene, width: u16, height: u16) -> Buffer {
+        render_with_metrics(scene, width, height, CellMetrics::UNIT)
+    }
+
+    fn render_with_metrics(
+        scene: &Scene,
+        width: u16,
+        height: u16,
+        cell_metrics: CellMetrics,
+    ) -> Buffer {
+        render_with_params(
+            scene,
+            width,
+            height,
+            &RenderParams {
+                base_color: BASE,
+                cell_metrics,
+                color_depth: ColorDepth::TrueColor,
+                sub_cell_mode: SubCellMode::Quadrant,
+            },
+        )
+    }
+
+    fn render_with_params(scene: &Scene, width: u16, height: u16, params: &RenderParams) -> Buffer {
+        let area = layout::Rect::new(0, 0, width, height);
+        let mut buffer = Buffer::empty(area);
+        Renderer::new().render_to_buffer(scene, &mut buffer, area, params);
+        buffer
+    }
+
+    fn lines(buffer: &Buffer) -> Vec<String> {
+        let area = buffer.area;
+        (area.top()..area.bottom())
+            .map(|y| {
+                (area.left()..area.right())
+                    .map(|x| buffer.get(x, y).symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fill_covers_cells_with_centers_inside() {
+        let mut scene = Scene::new();
+        let rect = Rect::new(1.0, 1.0, 3.4, 2.0);
+        scene.fill(Fill::NonZero, Affine::IDENTITY, Color::RED, None, &rect);
+        let buffer = render(&scene, 5, 3);
+
+        let red = ratatui::style::Color::Rgb(255, 0, 0);
+        let base = ratatui::style::Color::Rgb(0, 0, 0);
+        assert_eq!(buffer.get(0, 1).bg, base);
+        assert_eq!(buffer.get(1, 1).bg, red);
+        assert_eq!(buffer.get(2, 1).bg, red);
+        assert_eq!(buffer.get(3, 1).bg, base);
+        assert_eq!(buffer.get(1, 0).bg, base);
+        assert_eq!(buffer.get(1, 2).bg, base);
+    }
+
+    #[test]
+    fn fill_follows_the_fill_rule() {
+        // Two nested squares turning the same way: the inner one is a hole
+        // only with the even-odd rule.
+        let mut path = Rect::new(0.0, 0.0, 5.0, 5.0).to_path(0.1);
+        path.extend(Rect::new(1.0, 1.0, 4.0, 4.0).path_elements(0.1));
+        let red = ratatui::style::Color::Rgb(255, 0, 0);
+        let base = ratatui::style::Color::Rgb(0, 0, 0);
+
+        for (style, center) in [(Fill::NonZero, red), (Fill::EvenOdd, base)] {
+            let mut scene = Scene::new();
+            scene.fill(style, Affine::IDENTITY, Color::RED, None, &path);
+            let buffer = render(&scene, 5, 5);
+            assert_eq!(buffer.get(0, 0).bg, red);
+            assert_eq!(buffer.get(2, 2).bg, center, "{style:?}");
+        }
+    }
+
+    #[test]
+    fn fill_uses_gradient_at_cell_center() {
+        let mut scene = Scene::new();
+        let gradient = Gradient::new_linear((0.0, 0.0), (4.0, 0.0))
+            .with_stops([Color::BLACK, Color::WHITE].as_slice());
+        let rect = Rect::new(0.0, 0.0, 4.0, 1.0);
+        scene.fill(Fill::NonZero, Affine::IDENTITY, &gradient, None, &rect);
+        let buffer = render(&scene, 4, 1);
+
+        assert_eq!(buffer.get(0, 0).bg, ratatui::style::Color::Rgb(32, 32, 32));
+        assert_eq!(
+            buffer.get(3, 0).bg,
+            ratatui::style::Color::Rgb(223, 223, 223)
+        );
+    }
+
+    #[test]
+    fn stroke_draws_boxes_and_lines() {
+        let mut scene = Scene::new();
+        let stroke = Stroke::new(1.0);
+        scene.stroke(
+            &stroke,
+            Affine::IDENTITY,
+            Color::WHITE,
+            None,
+            &Rect::new(0.5, 0.5, 3.5, 2.5),
+        );
+        scene.stroke(
+            &stroke,
+            Affine::translate((4.0, 0.0)),
+            Color::WHITE,
+            None,
+            &RoundedRect::new(0.5, 0.5, 3.5, 2.5, 1.0),
+        );
+        scene.stroke(
+            &stroke,
+            Affine::IDENTITY,
+            Color::WHITE,
+            None,
+            &Line::new((0.0, 3.5), (8.0, 3.5)),
+        );
+        let buffer = render(&scene, 8, 4);
+
+        assert_eq!(
+            lines(&buffer),
+            ["┌──┐╭──╮", "│  ││  │", "└──┘╰──╯", "────────"]
+        );
+    }
+
+    #[test]
+    fn adjacent_boxes_merge_into_junctions() {
+        let mut scene = Scene::new();
+        let light = Stroke::new(1.0);
+        let heavy = Stroke::new(3.0);
+        let double = Stroke::new(5.0);
+        let rect = Rect::new(0.5, 0.5, 3.5, 2.5);
+        let mut stroke = |style: &Stroke, x: f64, y: f64| {
+            scene.stroke(style, Affine::translate((x, y)), Color::WHITE, None, &rect);
+        };
+        stroke(&light, 0.0, 0.0);
+        stroke(&light, 3.0, 0.0);
+        stroke(&heavy, 0.0, 2.0);
+        stroke(&light, 3.0, 2.0);
+        stroke(&double, 7.0, 0.0);
+        let buffer = render(&scene, 11, 5);
+
+        assert_eq!(
+            lines(&buffer),
+            [
+                "┌──┬──┐╔══╗",
+                "│  │  │║  ║",
+                "┢━━╅──┤╚══╝",
+                "┃  ┃  │    ",
+                "┗━━┹──┘    ",
+            ]
+        );
+    }
+
+    #[test]
+    fn text_is_written_by_grapheme() {
+        let mut scene = Scene::new();
+        scene.draw_text(
+            Affine::translate((1.0, 0.0)),
+            "a語e\u{301}!",
+            Rect::new(0.0, 0.0, 5.0, 1.0),
+            Color::WHITE,
+        );
+        let buffer = render(&scene, 7, 1);
+
+        assert_eq!(lines(&buffer), [" a語 e\u{301}! "]);
+        assert_eq!(
+            buffer.get(1, 0).fg,
+            ratatui::style::Color::Rgb(255, 255, 255)
+        );
+    }
+
+    #[test]
+    fn scene_is_scaled_to_cells() {
+        let mut scene = Scene::new();
+        let stroke = Stroke::new(1.0);
+        scene.stroke(
+            &stroke,
+            Affine::IDENTITY,
+            Color::WHITE,
+            None,
+            &RoundedRect::new(0.5, 0.5, 47.5, 35.5, 4.0),
+        );
+        scene.draw_text(
+            Affine::IDENTITY,
+            "Hi",
+            Rect::new(16.0, 16.0, 32.0, 34.0),
+            Color::WHITE,
+        );
+        let buffer = render_with_metrics(&scene, 6, 3, CellMetrics::TERMINAL);
+
+        assert_eq!(lines(&buffer), ["╭────╮", "│ Hi │", "╰────╯"]);
+    }
+
+    #[test]
+    fn render_stays_within_area() {
+        let mut scene = Scene::new();
+        let rect = Rect::new(-2.0, -2.0, 10.0, 10.0);
+        scene.fill(Fill::NonZero, Affine::IDENTITY, Color::RED, None, &rect);
+
+        let mut buffer = Buffer::empty(layout::Rect::new(0, 0, 4, 4));
+        let area = layout::Rect::new(1, 1, 2, 2);
+        Renderer::new().render_to_buffer(
+            &scene,
+            &mut buffer,
+            area,
+            &RenderParams {
+                base_color: BASE,
+                cell_metrics: CellMetrics::UNIT,
+                color_depth: ColorDepth::TrueColor,
+                sub_cell_mode: SubCellMode::Quadrant,
+            },
+        );
+
+        let red = ratatui::style::Color::Rgb(255, 0, 0);
+        assert_eq!(buffer.get(1, 1).bg, red);
+        assert_eq!(buffer.get(2, 2).bg, red);
+        assert_eq!(buffer.get(0, 0).bg, ratatui::style::Color::Reset);
+        assert_eq!(buffer.get(3, 3).bg, ratatui::style::Color::Reset);
+    }
+
+    #[test]
+    fn translucent_paint_is_blended_with_cell() {
+        let mut scene = Scene::new();
+        let veil = Color::rgba8(255, 255, 255, 128);
+        scene.draw_text(
+            Affine::IDENTITY,
+            "ab",
+            Rect::new(0.0, 0.0, 2.0, 1.0),
+            Color::RED,
+        );
+        scene.fill(
+            Fill::NonZero,
+            Affine::IDENTITY,
+            veil,
+            None,
+            &Rect::new(1.0, 0.0, 3.0, 1.0),
+        );
+        scene.draw_text(
+            Affine::translate((2.0, 0.0)),
+            "c",
+            Rect::new(0.0, 0.0, 1.0, 1.0),
+            veil,
+        );
+        let buffer = render(&scene, 3, 1);
+
+        use ratatui::style::Color::Rgb;
+        assert_eq!(lines(&buffer), ["abc"]);
+        assert_eq!(
+            (buffer.get(0, 0).fg, buffer.get(0, 0).bg),
+            (Rgb(255, 0, 0), Rgb(0, 0, 0))
+        );
+        // The fill tints both the glyph and the background under it.
+        assert_eq!(
+            (buffer.get(1, 0).fg, buffer.get(1, 0).bg),
+            (Rgb(255, 128, 128), Rgb(128, 128, 128))
+        );
+        // Translucent text is blended with the background it is drawn on.
+        assert_eq!(buffer.get(2, 0).fg, Rgb(192, 192, 192));
+    }
+
+    #[test]
+    fn colors_are_reduced_to_depth() {
+        let mut scene = Scene::new();
+        let highlight = Color::rgb8(0x5c, 0xc4, 0xff);
+        let rect = Rect::new(0.0, 0.0, 1.0, 1.0);
+        scene.fill(Fill::NonZero, Affine::IDENTITY, highlight, None, &rect);
+        let params = |color_depth| RenderParams {
+            base_color: Color::rgb8(0x29, 0x29, 0x29),
+            cell_metrics: CellMetrics::UNIT,
+            color_depth,
+            sub_cell_mode: SubCellMode::Quadrant,
+        };
+
+        let buffer = render_with_params(&scene, 2, 1, &params(ColorDepth::Ansi256));
+        assert_eq!(buffer.get(0, 0).bg, ratatui::style::Color::Indexed(81));
+        assert_eq!(buffer.get(1, 0).bg, ratatui::style::Color::Indexed(235));
+
+        let buffer = render_with_params(&scene, 2, 1, &params(ColorDepth::Monochrome));
+        assert_eq!(buffer.get(0, 0).bg, ratatui::style::Color::Reset);
+        assert!(buffer.get(0, 0).modifier.contains(Modifier::REVERSED));
+        assert!(!buffer.get(1, 0).modifier.contains(Modifier::REVERSED));
+    }
+
+    #[test]
+    fn layers_clip_cell_writes() {
+        let mut scene = Scene::new();
+        let line = Rect::new(0.0, 0.0, 6.0, 1.0);
+        scene.push_layer(
+            BlendMode::default(),
+            1.0,
+            Affine::IDENTITY,
+            &Rect::new(1.0, 0.0, 5.0, 3.0),
+        );
+        scene.push_layer(
+            BlendMode::default(),
+            1.0,
+            Affine::IDENTITY,
+            &Rect::new(0.0, 1.0, 6.0, 2.0),
+        );
+        for row in 0..3 {
+            let transform = Affine::translate((0.0, f64::from(row)));
+            scene.draw_text(transform, "abcd語f", line, Color::WHITE);
+        }
+        scene.pop_layer();
+        scene.draw_text(Affine::translate((0.0, 2.0)), "abcdef", line, Color::WHITE);
+        scene.pop_layer();
+        scene.draw_text(Affine::IDENTITY, "x", line, Color::WHITE);
+        let buffer = render(&scene, 6, 3);
+
+        // The wide grapheme doesn't fit in the clip.
+        assert_eq!(lines(&buffer), ["x     ", " bcd  ", " bcde "]);
+    }
+
+    #[test]
+    fn translucent_layers_are_blended_with_backdrop() {
+        let mut scene = Scene::new();
+        let rect = Rect::new(0.0, 0.0, 2.0, 1.0);
+        scene.draw_text(Affine::IDENTITY, "ab", rect, Color::WHITE);
+        for alpha in [0.75, 0.25] {
+            let x = if alpha > 0.5 { 0.0 } else { 1.0 };
+            let cell = Rect::new(x, 0.0, x + 1.0, 1.0);
+            scene.push_layer(BlendMode::default(), alpha, Affine::IDENTITY, &cell);
+            scene.fill(Fill::NonZero, Affine::IDENTITY, Color::RED, None, &rect);
+            scene.draw_text(Affine::IDENTITY, "xy", rect, Color::WHITE);
+            scene.pop_layer();
+        }
+        let buffer = render(&scene, 2, 1);
+
+        use ratatui::style::Color::Rgb;
+        // The most opaque of the layer and the backdrop gives the symbol.
+        assert_eq!(lines(&buffer), ["xb"]);
+        assert_eq!(buffer.get(0, 0).bg, Rgb(191, 0, 0));
+        assert_eq!(buffer.get(0, 0).fg, Rgb(191, 191, 191));
+        assert_eq!(buffer.get(1, 0).bg, Rgb(64, 0, 0));
+        assert_eq!(buffer.get(1, 0).fg, Rgb(255, 191, 191));
+    }
+
+    #[test]
+    fn circles_are_drawn_with_quadrants() {
+        let mut scene = Scene::new();
+        let circle = Circle::new((3.0, 3.0), 2.5);
+        scene.fill(Fill::NonZero, Affine::IDENTITY, Color::RED, None, &circle);
+        let buffer = render(&scene, 6, 6);
+
+        assert_eq!(
+            lines(&buffer),
+            ["  ▄▄  ", "      ", "▐    ▌", "▐    ▌", "      ", "  ▀▀  "]
+        );
+        // Cells inside the circle are filled like rects.
+        assert_eq!(buffer.get(3, 3).bg, ratatui::style::Color::Rgb(255, 0, 0));
+    }
+
+    #[test]
+    fn diagonal_lines_are_drawn_with_braille() {
+        let mut scene = Scene::new();
+        let line = Line::new((0.0, 0.0), (4.0, 2.0));
+        scene.stroke(
+            &Stroke::new(0.25),
+            Affine::IDENTITY,
+            Color::WHITE,
+            None,
+            &line,
+        );
+        let params = RenderParams {
+            base_color: BASE,
+            cell_metrics: CellMetrics::UNIT,
+            color_depth: ColorDepth::TrueColor,
+            sub_cell_mode: SubCellMode::Braille,
+        };
+        let buffer = render_with_params(&scene, 4, 2, &params);
+
+        assert_eq!(lines(&buffer), ["⠑⢄  ", "  ⠑⢄"]);
+    }
+
+    #[test]
+    fn damage_limits_redrawn_cells() {
+        let area = layout::Rect::new(0, 0, 6, 2);
+        let params = RenderParams {
+            base_color: BASE,
+            cell_metrics: CellMetrics::UNIT,
+            color_depth: ColorDepth::TrueColor,
+            sub_cell_mode: SubCellMode::Quadrant,
+        };
+        let line = Rect::new(0.0, 0.0, 6.0, 1.0);
+        let mut renderer = Renderer::new();
+        let mut buffer = Buffer::empty(area);
+        let mut scene = Scene::new();
+        scene.draw_text(Affine::IDENTITY, "abcdef", line, Color::WHITE);
+        renderer.render_to_buffer(&scene, &mut buffer, area, &params);
+
+        let mut scene = Scene::new();
+        scene.draw_text(Affine::IDENTITY, "ABCDEF", line, Color::WHITE);
+        scene.draw_text(Affine::translate((0.0, 1.0)), "ghijkl", line, Color::WHITE);
+        let damage = [Rect::new(1.0, 0.0, 3.0, 2.0), Rect::new(5.0, 1.0, 6.0, 2.0)];
+        renderer.render_damage_to_buffer(&scene, &mut buffer, area, &damage, &params);
+
+        assert_eq!(lines(&buffer), ["aBCdef", " hi  l"]);
+    }
+}
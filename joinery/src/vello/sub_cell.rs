@@ -0,0 +1,106 @@
+//! Block and braille characters used to draw shapes finer than a cell.
+
+/// How shapes that don't follow the cell grid, such as circles, arcs and
+/// diagonal lines, are rasterized.
+///
+/// Each cell is split into a grid of sub-pixels, and a shape is drawn with the
+/// character whose sub-pixels best match the part of the cell it covers. Cells
+/// that are completely covered are filled like any other cell, so a large
+/// shape only has characters along its edges.
+///
+/// Rects and rounded rects are always snapped to whole cells, so that widget
+/// backgrounds and borders line up with text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SubCellMode {
+    /// Shapes are only drawn in the cells whose centers they cover, and
+    /// strokes of paths are dropped.
+    Off,
+    /// Upper and lower half blocks (`▀`, `▄`): 1×2 sub-pixels. These are
+    /// available in nearly every terminal font, including the Linux console.
+    HalfBlock,
+    /// Quadrant blocks (`▖`, `▚`, `▟`…): 2×2 sub-pixels.
+    #[default]
+    Quadrant,
+    /// Braille patterns (`⠇`, `⣿`…): 2×4 dots.
+    Braille,
+}
+
+impl SubCellMode {
+    /// The number of columns and rows of sub-pixels in a cell.
+    pub(crate) fn grid(self) -> (u8, u8) {
+        match self {
+            SubCellMode::Off => (1, 1),
+            SubCellMode::HalfBlock => (1, 2),
+            SubCellMode::Quadrant => (2, 2),
+            SubCellMode::Braille => (2, 4),
+        }
+    }
+
+    /// The bit of the sub-pixel at `col` and `row` in a mask.
+    pub(crate) fn bit(self, col: u8, row: u8) -> u8 {
+        match self {
+            // Braille dots are numbered down the left column, then down the
+            // right one, with the bottom row added last.
+            SubCellMode::Braille => match (col, row) {
+                (0, 3) => 0x40,
+                (1, 3) => 0x80,
+                _ => 1 << (col * 3 + row),
+            },
+            _ => {
+                let (cols, _) = self.grid();
+                1 << (row * cols + col)
+            }
+        }
+    }
+
+    /// The mask with every sub-pixel set.
+    pub(crate) fn full_mask(self) -> u8 {
+        let (cols, rows) = self.grid();
+        (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (col, row)))
+            .fold(0, |mask, (col, row)| mask | self.bit(col, row))
+    }
+
+    /// The character showing the sub-pixels set in `mask`.
+    pub(crate) fn symbol(self, mask: u8) -> char {
+        const HALF_BLOCKS: [char; 4] = [' ', '▀', '▄', '█'];
+        const QUADRANTS: [char; 16] = [
+            ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+        ];
+        match self {
+            SubCellMode::Off => {
+                if mask == 0 {
+                    ' '
+                } else {
+                    '█'
+                }
+            }
+            SubCellMode::HalfBlock => HALF_BLOCKS[usize::from(mask & 0b11)],
+            SubCellMode::Quadrant => QUADRANTS[usize::from(mask & 0b1111)],
+            SubCellMode::Braille => char::from_u32(0x2800 + u32::from(mask)).unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_map_to_symbols() {
+        let quadrant = SubCellMode::Quadrant;
+        let top_right = quadrant.bit(1, 0);
+        let bottom_left = quadrant.bit(0, 1);
+        assert_eq!(quadrant.symbol(top_right | bottom_left), '▞');
+        assert_eq!(quadrant.symbol(quadrant.full_mask()), '█');
+
+        let half_block = SubCellMode::HalfBlock;
+        assert_eq!(half_block.symbol(half_block.bit(0, 1)), '▄');
+
+        let braille = SubCellMode::Braille;
+        let left_column = (0..4).fold(0, |mask, row| mask | braille.bit(0, row));
+        assert_eq!(braille.symbol(left_column), '⡇');
+        assert_eq!(braille.symbol(braille.bit(1, 3)), '⢀');
+        assert_eq!(braille.full_mask(), 0xFF);
+    }
+}
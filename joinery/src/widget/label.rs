@@ -214,6 +214,7 @@ impl Widget for Label {
             None
         };
         self.text_layout.set_max_advance(max_advance);
+        self.text_layout.set_engine(ctx.text_engine());
         if self.text_layout.needs_rebuild() {
             let (font_ctx, layout_ctx) = ctx.text_contexts();
             self.text_layout.rebuild(font_ctx, layout_ctx);
@@ -281,8 +282,10 @@ mod tests {
     use super::*;
     use crate::assert_render_snapshot;
     use crate::testing::TestHarness;
+    use crate::text2::TextEngine;
     use crate::theme::{PRIMARY_DARK, PRIMARY_LIGHT};
     use crate::widget::{Flex, SizedBox};
+    use crate::CellMetrics;
 
     #[test]
     fn simple_label() {
@@ -294,6 +297,17 @@ mod tests {
         assert_render_snapshot!(harness, "hello");
     }
 
+    #[test]
+    fn label_with_cell_text_engine() {
+        let label = Label::new("Hello 世界\nagain");
+
+        let mut harness = TestHarness::create(label);
+        harness.set_text_engine(TextEngine::Cells(CellMetrics::UNIT));
+
+        let label = harness.root_widget().downcast::<Label>().unwrap();
+        assert_eq!(label.deref().text_layout.size(), Size::new(10.0, 2.0));
+    }
+
     #[test]
     fn styled_label() {
         let label = Label::new("The quick brown fox jumps over the lazy dog")
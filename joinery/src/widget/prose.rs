@@ -183,7 +183,7 @@ impl Widget for Prose {
 
     fn on_text_event(&mut self, ctx: &mut EventCtx, event: &TextEvent) {
         // If focused on a link and enter pressed, follow it?
-        let result = self.text_layout.text_event(event);
+        let result = self.text_layout.text_event(ctx, event);
         if result.is_handled() {
             ctx.set_handled();
             // TODO: only some handlers need this repaint
@@ -248,6 +248,7 @@ impl Widget for Prose {
             None
         };
         self.text_layout.set_max_advance(max_advance);
+        self.text_layout.set_engine(ctx.text_engine());
         if self.text_layout.needs_rebuild() {
             let (font_ctx, layout_ctx) = ctx.text_contexts();
             self.text_layout.rebuild(font_ctx, layout_ctx);
@@ -3,14 +3,14 @@
 
 //! A widget which splits an area in two, with a settable ratio, and optional draggable resizing.
 
-use crate::vello::Scene;
+use crate::vello::{peniko::BlendMode, Scene};
 use accesskit::Role;
 use smallvec::{smallvec, SmallVec};
 use tracing::{trace, trace_span, warn, Span};
 
 use crate::dpi::LogicalPosition;
 use crate::event::PointerButton;
-use crate::kurbo::Line;
+use crate::kurbo::{Affine, Line};
 use crate::paint_scene_helpers::{fill_color, stroke};
 use crate::widget::flex::Axis;
 use crate::widget::{WidgetMut, WidgetPod, WidgetRef};
@@ -566,8 +566,27 @@ impl Widget for Split {
         } else {
             self.paint_stroked_bar(ctx, scene);
         }
+
+        // Clip each child to its side of the bar, so that overflowing
+        // content doesn't cover the bar or the other child.
+        let size = ctx.size();
+        let (edge1, edge2) = self.bar_edges(size);
+        let (area1, area2) = match self.split_axis {
+            Axis::Horizontal => (
+                Rect::new(0.0, 0.0, edge1, size.height),
+                Rect::new(edge2, 0.0, size.width, size.height),
+            ),
+            Axis::Vertical => (
+                Rect::new(0.0, 0.0, size.width, edge1),
+                Rect::new(0.0, edge2, size.width, size.height),
+            ),
+        };
+        scene.push_layer(BlendMode::default(), 1., Affine::IDENTITY, &area1);
         self.child1.paint(ctx, scene);
+        scene.pop_layer();
+        scene.push_layer(BlendMode::default(), 1., Affine::IDENTITY, &area2);
         self.child2.paint(ctx, scene);
+        scene.pop_layer();
     }
 
     fn accessibility_role(&self) -> Role {
@@ -0,0 +1,29 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::testing::{ModularWidget, TestHarness};
+use crate::*;
+
+#[test]
+fn anim_frames_stop_when_not_requested() {
+    // Asks for 3 frames, and counts the frames it gets.
+    let frames = Rc::new(Cell::new(0));
+    let widget =
+        ModularWidget::new(frames.clone()).lifecycle_fn(|frames, ctx, event| match event {
+            LifeCycle::WidgetAdded => ctx.request_anim_frame(),
+            LifeCycle::AnimFrame(_) => {
+                frames.set(frames.get() + 1);
+                if frames.get() < 3 {
+                    ctx.request_anim_frame();
+                }
+            }
+            _ => {}
+        });
+
+    let mut harness = TestHarness::create(widget);
+    for _ in 0..5 {
+        harness.process_window_event(WindowEvent::AnimFrame);
+    }
+    assert_eq!(frames.get(), 3);
+    assert!(!harness.root_widget().state().request_anim);
+}
@@ -0,0 +1,35 @@
+use crate::testing::{widget_ids, TestHarness};
+use crate::widget::{Button, Flex, Label};
+
+#[test]
+fn only_changed_widgets_are_damaged() {
+    let [button, label] = widget_ids();
+    let widget = Flex::column()
+        .with_child_id(Button::new("Hello"), button)
+        .with_child_id(Label::new("World"), label);
+
+    let mut harness = TestHarness::create(widget);
+    let window = harness.root_widget().state().window_layout_rect();
+    assert!(harness.redraw_damage().contains(&window));
+    assert_eq!(harness.redraw_damage(), []);
+
+    // Hovering the button repaints it, and its ancestors, but doesn't change
+    // anything else.
+    harness.mouse_move_to(button);
+    let button_rect = harness.get_widget(button).state().window_layout_rect();
+    let damage = harness.redraw_damage();
+    assert!(!damage.is_empty());
+    assert!(damage.iter().all(|rect| *rect == button_rect));
+
+    // Moving a widget damages both its old and new areas.
+    let label_rect = harness.get_widget(label).state().window_layout_rect();
+    harness.edit_root_widget(|mut root| {
+        let mut flex = root.downcast::<Flex>();
+        flex.insert_spacer(0, 1.0);
+    });
+    let damage = harness.redraw_damage();
+    let moved_label_rect = harness.get_widget(label).state().window_layout_rect();
+    assert_ne!(moved_label_rect, label_rect);
+    assert!(damage.contains(&label_rect));
+    assert!(damage.contains(&moved_label_rect));
+}
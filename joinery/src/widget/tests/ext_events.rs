@@ -0,0 +1,38 @@
+use std::thread;
+
+use crate::testing::TestHarness;
+use crate::widget::{Label, WidgetMut};
+use crate::*;
+
+#[test]
+fn other_threads_can_submit_actions_and_callbacks() {
+    let mut harness = TestHarness::create(Label::new("Loading"));
+    let label_id = harness.root_widget().id();
+
+    let sink = harness.ext_event_sink();
+    thread::spawn(move || {
+        sink.submit_action(Action::TextEntered("loaded".into()), label_id)
+            .unwrap();
+        sink.run_on_driver(|ctx| {
+            let mut label: WidgetMut<Label> = ctx.get_root();
+            label.set_text("Loaded");
+        })
+        .unwrap();
+    })
+    .join()
+    .unwrap();
+
+    // Nothing happens until the harness handles external events.
+    assert_eq!(harness.pop_action(), None);
+    harness.process_ext_events();
+
+    assert_eq!(
+        harness.pop_action(),
+        Some((Action::TextEntered("loaded".into()), label_id))
+    );
+    let label = harness.get_widget(label_id);
+    assert_eq!(
+        label.downcast::<Label>().unwrap().text().to_string(),
+        "Loaded"
+    );
+}
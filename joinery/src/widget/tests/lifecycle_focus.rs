@@ -9,7 +9,7 @@ use std::rc::Rc;
 use smallvec::smallvec;
 
 use crate::testing::{widget_ids, ModularWidget, ReplaceChild, TestHarness, TestWidgetExt as _};
-use crate::widget::Flex;
+use crate::widget::{Flex, SizedBox};
 use crate::*;
 
 #[cfg(FALSE)]
@@ -238,3 +238,25 @@ fn resign_focus_on_disable() {
     assert_eq!(harness.window().focus_chain(), &[focus_2]);
     assert_eq!(harness.window().focus, None);
 }
+
+/// Check that focus can be given from outside the widget tree, as for the
+/// initial focus of an app.
+#[test]
+fn set_focus_from_render_root() {
+    let [id_1, id_2] = widget_ids();
+
+    let widget = Flex::row()
+        .with_child_id(SizedBox::empty(), id_1)
+        .with_child_id(SizedBox::empty(), id_2);
+    let mut harness = TestHarness::create(widget);
+    assert!(harness.focused_widget().is_none());
+
+    harness.set_focus(Some(id_2));
+    assert_eq!(
+        harness.focused_widget().map(|widget| widget.id()),
+        Some(id_2)
+    );
+
+    harness.set_focus(None);
+    assert!(harness.focused_widget().is_none());
+}
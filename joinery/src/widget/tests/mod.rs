@@ -3,9 +3,14 @@
 
 // TODO - See https://github.com/PoignardAzur/masonry-rs/issues/58
 
+mod anim_frame;
+mod damage;
+mod ext_events;
 mod layout;
 mod lifecycle_basic;
 mod lifecycle_disable;
 mod lifecycle_focus;
+mod promises;
 mod safety_rails;
 mod status_change;
+mod timers;
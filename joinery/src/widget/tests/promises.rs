@@ -0,0 +1,58 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::promise::PromiseToken;
+use crate::testing::{widget_ids, ModularWidget, TestHarness};
+use crate::widget::Flex;
+use crate::*;
+
+/// A widget computing a value in the background when added, and recording
+/// the promise results it receives.
+fn computing_widget(value: u32, received: Rc<RefCell<Vec<u32>>>) -> impl Widget {
+    let token: Option<PromiseToken<u32>> = None;
+    ModularWidget::new(token).lifecycle_fn(move |token, ctx, event| match event {
+        LifeCycle::WidgetAdded => {
+            *token = Some(ctx.compute_in_background(move |_| value * 2));
+        }
+        LifeCycle::PromiseResult(result) => {
+            received.borrow_mut().push(result.get(token.unwrap()));
+        }
+        _ => {}
+    })
+}
+
+#[test]
+fn promise_results_are_sent_to_their_widget() {
+    let [first, second] = widget_ids();
+    let first_received = Rc::new(RefCell::new(Vec::new()));
+    let second_received = Rc::new(RefCell::new(Vec::new()));
+    let widget = Flex::column()
+        .with_child_id(computing_widget(1, first_received.clone()), first)
+        .with_child_id(computing_widget(2, second_received.clone()), second);
+
+    let _harness = TestHarness::create(widget);
+    assert_eq!(*first_received.borrow(), [2]);
+    assert_eq!(*second_received.borrow(), [4]);
+}
+
+#[test]
+fn workers_can_send_several_results() {
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let widget = {
+        let received = received.clone();
+        let token = PromiseToken::<String>::new();
+        ModularWidget::new(()).lifecycle_fn(move |_, ctx, event| match event {
+            LifeCycle::WidgetAdded => ctx.run_in_background(move |worker_ctx| {
+                worker_ctx.resolve(token, "loading".to_string());
+                worker_ctx.resolve(token, "done".to_string());
+            }),
+            LifeCycle::PromiseResult(result) => {
+                received.borrow_mut().push(result.get(token));
+            }
+            _ => {}
+        })
+    };
+
+    let _harness = TestHarness::create(widget);
+    assert_eq!(*received.borrow(), ["loading", "done"]);
+}
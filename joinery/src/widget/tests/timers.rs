@@ -0,0 +1,48 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::testing::{widget_ids, ModularWidget, TestHarness};
+use crate::widget::Flex;
+use crate::*;
+
+/// A widget requesting a timer when added, and recording the timers it
+/// receives.
+fn timer_widget(delay: Duration, fired: Rc<RefCell<Vec<TimerToken>>>) -> impl Widget {
+    ModularWidget::new(None).lifecycle_fn(move |token, ctx, event| match event {
+        LifeCycle::WidgetAdded => *token = Some(ctx.request_timer(delay)),
+        LifeCycle::Timer(fired_token) => {
+            assert_eq!(Some(*fired_token), *token);
+            fired.borrow_mut().push(*fired_token);
+        }
+        _ => {}
+    })
+}
+
+#[test]
+fn timers_are_sent_to_their_widget() {
+    let [early, late] = widget_ids();
+    let early_fired = Rc::new(RefCell::new(Vec::new()));
+    let late_fired = Rc::new(RefCell::new(Vec::new()));
+    let widget = Flex::column()
+        .with_child_id(
+            timer_widget(Duration::from_millis(100), early_fired.clone()),
+            early,
+        )
+        .with_child_id(
+            timer_widget(Duration::from_millis(300), late_fired.clone()),
+            late,
+        );
+
+    let mut harness = TestHarness::create(widget);
+    harness.move_timers_forward(Duration::from_millis(50));
+    assert!(early_fired.borrow().is_empty());
+
+    harness.move_timers_forward(Duration::from_millis(50));
+    assert_eq!(early_fired.borrow().len(), 1);
+    assert!(late_fired.borrow().is_empty());
+
+    harness.move_timers_forward(Duration::from_secs(1));
+    assert_eq!(early_fired.borrow().len(), 1);
+    assert_eq!(late_fired.borrow().len(), 1);
+}
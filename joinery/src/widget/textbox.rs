@@ -6,7 +6,7 @@ use crate::vello::{
     Scene,
 };
 use accesskit::Role;
-use kurbo::{Affine, Point, Size, Stroke};
+use kurbo::{Affine, Point, Rect, Size, Stroke, Vec2};
 use parley::{
     layout::Alignment,
     style::{FontFamily, FontStack},
@@ -272,6 +272,7 @@ impl Widget for Textbox {
             None
         };
         self.editor.set_max_advance(max_advance);
+        self.editor.set_engine(ctx.text_engine());
         if self.editor.needs_rebuild() {
             let (font_ctx, layout_ctx) = ctx.text_contexts();
             self.editor.rebuild(font_ctx, layout_ctx);
@@ -325,14 +326,23 @@ impl Widget for Textbox {
         );
         let origin = ctx.widget_state.window_origin();
         if ctx.widget_state.has_focus {
+            // Input methods show their candidates next to the cursor, or next
+            // to the whole textbox if there is no cursor.
+            let ime_area = match self.editor.cursor_line() {
+                Some(line) => {
+                    Rect::from_points(line.p0, line.p1)
+                        + Vec2::new(TEXTBOX_PADDING, TEXTBOX_PADDING)
+                }
+                None => size.to_rect(),
+            } + origin.to_vec2();
             ctx.signal(crate::render_root::RenderRootSignal::ImeMoved(
                 LogicalPosition {
-                    x: origin.x,
-                    y: origin.y + size.height,
+                    x: ime_area.x0,
+                    y: ime_area.y0,
                 },
                 LogicalSize {
-                    width: size.width,
-                    height: size.height,
+                    width: ime_area.width(),
+                    height: ime_area.height(),
                 },
             ));
         }
@@ -358,3 +368,80 @@ impl Widget for Textbox {
         Some(self.editor.text().as_str().chars().take(100).collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::event::{ElementState, KeyEvent};
+    use crate::terminal::keyboard::{Key, ModifiersState};
+    use crate::testing::{widget_ids, TestHarness, TestWidgetExt};
+    use crate::{Action, LocalClipboard};
+
+    fn shortcut(chr: &str) -> TextEvent {
+        let event = KeyEvent {
+            logical_key: Key::Character(chr.into()),
+            text: Some(chr.into()),
+            state: ElementState::Pressed,
+            repeat: false,
+        };
+        TextEvent::KeyboardKey(event, ModifiersState::CONTROL)
+    }
+
+    #[test]
+    fn paste_is_inserted_at_once() {
+        let [textbox_id] = widget_ids();
+        let widget = Textbox::new("ab").with_id(textbox_id);
+
+        let mut harness = TestHarness::create(widget);
+        harness.mouse_click_on(textbox_id);
+        assert_eq!(
+            harness.focused_widget().map(|widget| widget.id()),
+            Some(textbox_id)
+        );
+
+        harness.process_text_event(TextEvent::Paste("one\ntwo\r\n".into()));
+        let Some((Action::TextChanged(text), id)) = harness.pop_action() else {
+            panic!("expected a single TextChanged action");
+        };
+        assert_eq!(id, textbox_id);
+        assert_eq!(text, "abone two ");
+        assert_eq!(harness.pop_action(), None);
+    }
+
+    #[test]
+    fn cut_and_paste_use_clipboard() {
+        let [textbox_id] = widget_ids();
+        let widget = Textbox::new("hello").with_id(textbox_id);
+
+        let mut harness = TestHarness::create(widget);
+        harness.set_clipboard(LocalClipboard::default());
+        harness.mouse_click_on(textbox_id);
+
+        harness.process_text_event(shortcut("a"));
+        harness.process_text_event(shortcut("x"));
+        assert_eq!(harness.clipboard().get_text().as_deref(), Some("hello"));
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::TextChanged(String::new()), textbox_id))
+        );
+
+        harness.process_text_event(shortcut("v"));
+        harness.process_text_event(shortcut("v"));
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::TextChanged("hello".into()), textbox_id))
+        );
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::TextChanged("hellohello".into()), textbox_id))
+        );
+
+        harness.process_text_event(shortcut("a"));
+        harness.process_text_event(shortcut("c"));
+        assert_eq!(
+            harness.clipboard().get_text().as_deref(),
+            Some("hellohello")
+        );
+        assert_eq!(harness.pop_action(), None);
+    }
+}
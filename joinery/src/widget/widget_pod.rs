@@ -31,6 +31,8 @@ pub struct WidgetPod<W> {
     pub(crate) state: WidgetState,
     pub(crate) inner: W,
     pub(crate) fragment: Scene,
+    /// The area of the window `fragment` covered the last time it was drawn.
+    pub(crate) window_paint_rect: Option<Rect>,
 }
 
 // --- MARK: GETTERS ---
@@ -53,6 +55,7 @@ impl<W: Widget> WidgetPod<W> {
             state,
             inner,
             fragment: Scene::new(),
+            window_paint_rect: None,
         }
     }
 
@@ -622,6 +625,42 @@ impl<W: Widget> WidgetPod<W> {
                         self.state.children_disabled_changed
                     }
                 }
+                InternalLifeCycle::RouteTimer { target, token } => {
+                    if *target == self.state.id {
+                        let token = *token;
+                        self.call_widget_method_with_checks("lifecycle", |widget_pod| {
+                            let mut inner_ctx = LifeCycleCtx {
+                                global_state: parent_ctx.global_state,
+                                widget_state: &mut widget_pod.state,
+                            };
+
+                            widget_pod
+                                .inner
+                                .lifecycle(&mut inner_ctx, &LifeCycle::Timer(token));
+                        });
+                        false
+                    } else {
+                        self.state.children.may_contain(target)
+                    }
+                }
+                InternalLifeCycle::RoutePromiseResult { target, result } => {
+                    if *target == self.state.id {
+                        let result = result.clone();
+                        self.call_widget_method_with_checks("lifecycle", |widget_pod| {
+                            let mut inner_ctx = LifeCycleCtx {
+                                global_state: parent_ctx.global_state,
+                                widget_state: &mut widget_pod.state,
+                            };
+
+                            widget_pod
+                                .inner
+                                .lifecycle(&mut inner_ctx, &LifeCycle::PromiseResult(result));
+                        });
+                        false
+                    } else {
+                        self.state.children.may_contain(target)
+                    }
+                }
                 InternalLifeCycle::RouteFocusChanged { old, new } => {
                     let this_changed = if *old == Some(self.state.id) {
                         Some(false)
@@ -688,7 +727,15 @@ impl<W: Widget> WidgetPod<W> {
                 );
                 return;
             }
-            LifeCycle::AnimFrame(_) => true,
+            LifeCycle::AnimFrame(_) => {
+                // Only widgets that asked for this frame get it, and they
+                // have to ask again for the next one.
+                std::mem::take(&mut self.state.request_anim)
+            }
+            // Timers and promise results are routed to their target with
+            // `RouteTimer` and `RoutePromiseResult`, so that containers
+            // forwarding these events don't pass them to their children.
+            LifeCycle::Timer(_) | LifeCycle::PromiseResult(_) => false,
             LifeCycle::DisabledChanged(ancestors_disabled) => {
                 self.state.update_focus_chain = true;
 
@@ -954,6 +1001,19 @@ impl<W: Widget> WidgetPod<W> {
         self.mark_as_visited();
         self.check_initialized("paint");
 
+        // The fragment of a widget includes the fragments of its children, so
+        // a widget is repainted whenever one of its descendants is. Only the
+        // widgets which changed themselves, or moved, damage the window.
+        let window_origin = parent_ctx.window_origin + self.state.origin.to_vec2();
+        let window_paint_rect = self.state.local_paint_rect + window_origin.to_vec2();
+        if self.state.is_damaged || self.window_paint_rect != Some(window_paint_rect) {
+            let damage = &mut parent_ctx.global_state.damage;
+            damage.extend(self.window_paint_rect);
+            damage.push(window_paint_rect);
+            self.window_paint_rect = Some(window_paint_rect);
+            self.state.is_damaged = false;
+        }
+
         if self.state.needs_paint {
             trace!(
                 "Painting widget '{}' #{}",
@@ -963,13 +1023,13 @@ impl<W: Widget> WidgetPod<W> {
 
             self.state.needs_paint = false;
             self.call_widget_method_with_checks("paint", |widget_pod| {
-                // TODO - Handle invalidation regions
                 let mut inner_ctx = PaintCtx {
                     global_state: parent_ctx.global_state,
                     widget_state: &widget_pod.state,
                     depth: parent_ctx.depth + 1,
                     debug_paint: parent_ctx.debug_paint,
                     debug_widget: parent_ctx.debug_widget,
+                    window_origin,
                 };
 
                 widget_pod.fragment.reset();
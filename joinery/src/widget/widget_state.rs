@@ -81,6 +81,9 @@ pub struct WidgetState {
 
     pub(crate) needs_layout: bool,
     pub(crate) needs_paint: bool,
+    /// This widget's own appearance may have changed, so the area it covers
+    /// has to be redrawn on screen. Unlike `needs_paint`, this isn't merged up.
+    pub(crate) is_damaged: bool,
     pub(crate) needs_accessibility_update: bool,
 
     /// Because of some scrolling or something, `parent_window_origin` needs to be updated.
@@ -163,6 +166,7 @@ impl WidgetState {
             is_hot: false,
             needs_layout: false,
             needs_paint: false,
+            is_damaged: false,
             needs_accessibility_update: false,
             needs_window_origin: false,
             is_active: false,
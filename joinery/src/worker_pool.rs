@@ -0,0 +1,96 @@
+//! Threads running the background tasks of widgets.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::contexts::WorkerFn;
+
+/// A fixed set of threads running [`WorkerFn`]s in the order they were
+/// spawned.
+///
+/// The threads are started by the first spawned worker, and stop once the pool
+/// is dropped and the workers already spawned are done.
+pub(crate) struct WorkerPool {
+    thread_count: usize,
+    sender: Option<Sender<WorkerFn>>,
+}
+
+impl WorkerPool {
+    /// Create a pool with one thread per core.
+    pub(crate) fn new() -> Self {
+        let thread_count = thread::available_parallelism().map_or(1, |count| count.get());
+        WorkerPool {
+            thread_count,
+            sender: None,
+        }
+    }
+
+    pub(crate) fn spawn(&mut self, worker: WorkerFn) {
+        let thread_count = self.thread_count;
+        let sender = self
+            .sender
+            .get_or_insert_with(|| start_threads(thread_count));
+        if sender.send(worker).is_err() {
+            tracing::error!("Worker pool has no thread left, dropping background task");
+        }
+    }
+}
+
+fn start_threads(thread_count: usize) -> Sender<WorkerFn> {
+    let (sender, receiver) = channel();
+    let receiver = Arc::new(Mutex::new(receiver));
+    for index in 0..thread_count {
+        let receiver = receiver.clone();
+        let spawned = thread::Builder::new()
+            .name(format!("joinery-worker-{index}"))
+            .spawn(move || run_workers(&receiver));
+        if let Err(err) = spawned {
+            tracing::error!("Failed to start worker thread: {err}");
+        }
+    }
+    sender
+}
+
+fn run_workers(receiver: &Mutex<Receiver<WorkerFn>>) {
+    loop {
+        // The lock is released before running the worker.
+        let worker = match receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => return,
+        };
+        let Ok(worker) = worker else {
+            return;
+        };
+        // A panicking task only loses its own result, not the thread.
+        if catch_unwind(AssertUnwindSafe(|| worker.run())).is_err() {
+            tracing::error!("Background task panicked");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workers_run_on_other_threads() {
+        let mut pool = WorkerPool::new();
+        let (sender, receiver) = channel();
+        let main_thread = thread::current().id();
+        for i in 0..4 {
+            let sender = sender.clone();
+            pool.spawn(WorkerFn::new(move || {
+                sender.send((i, thread::current().id())).unwrap();
+            }));
+        }
+        let mut results: Vec<_> = (0..4).map(|_| receiver.recv().unwrap()).collect();
+        results.sort_by_key(|(i, _)| *i);
+        assert_eq!(
+            results.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+            [0, 1, 2, 3]
+        );
+        assert!(results.iter().all(|(_, thread)| *thread != main_thread));
+    }
+}